@@ -0,0 +1,306 @@
+//! `motioner`: the headless command-line front end.
+//!
+//! Loads a project folder (its `code.motioner`) or a single `.motioner` file
+//! and checks, renders or exports it without opening a window, so it works
+//! on CI machines with no display.
+
+use anyhow::{anyhow, bail, Context};
+use motioner_core::dsl::{self, Diagnostic};
+use motioner_core::export::png_sequence::frame_file_name;
+use motioner_core::export::video::{RateControl, VideoCodec, VideoSettings};
+use motioner_core::export::{
+    ExportFormat, ExportOutcome, ExportProgress, ExportSettings, LoopSettings,
+};
+use motioner_core::project::Project;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage:
+  motioner check  <project>
+  motioner render <project> (--frame N | --time SECONDS) [--output FILE]
+  motioner export <project> [--format FORMAT] [--output PATH] [options]
+
+<project> is a project folder (see motioner.project), or a .motioner file.
+Output goes to <project folder>/export unless --output is given.
+
+render:
+  --frame N             0-based frame index at the project's fps
+  --time SECONDS        scene time to render
+
+export:
+  --format FORMAT       png (image sequence, default), gif, apng,
+                        mp4, webm or prores
+  --crf N               video quality (lower is better)
+  --bitrate KBPS        video bitrate instead of --crf
+  --ffmpeg PATH         ffmpeg executable (default: search PATH)
+  --loop N              GIF/APNG plays, 0 loops forever (default)
+  --no-dither           do not dither GIF colors
+";
+
+/// A command-line mistake: reported together with the usage text.
+#[derive(Debug)]
+struct UsageError(String);
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+fn usage(message: impl Into<String>) -> anyhow::Error {
+    UsageError(message.into()).into()
+}
+
+fn main() -> ExitCode {
+    let mut args = pico_args::Arguments::from_env();
+    if args.contains(["-h", "--help"]) {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match run(args) {
+        Ok(code) => code,
+        Err(err) if err.is::<UsageError>() => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            ExitCode::from(2)
+        }
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: pico_args::Arguments) -> anyhow::Result<ExitCode> {
+    let command: String = args
+        .subcommand()
+        .map_err(|e| usage(e.to_string()))?
+        .ok_or_else(|| usage("missing command"))?;
+    if !matches!(command.as_str(), "check" | "render" | "export") {
+        return Err(usage(format!("unknown command '{}'", command)));
+    }
+    let project = Project::load(
+        &args
+            .free_from_str::<PathBuf>()
+            .map_err(|_| usage("missing <project>"))?,
+    )?;
+
+    match command.as_str() {
+        "check" => {
+            finish(args)?;
+            Ok(check(&project))
+        }
+        "render" => {
+            let frame: Option<usize> = opt(&mut args, "--frame")?;
+            let time: Option<f32> = opt(&mut args, "--time")?;
+            let output: Option<PathBuf> = opt(&mut args, ["-o", "--output"])?;
+            finish(args)?;
+            render(&project, frame, time, output)
+        }
+        "export" => {
+            let format = match opt::<String>(&mut args, "--format")? {
+                Some(name) => parse_format(&name)?,
+                None => ExportFormat::PngSequence,
+            };
+            let mut video = VideoSettings {
+                ffmpeg_path: opt(&mut args, "--ffmpeg")?,
+                ..VideoSettings::default()
+            };
+            match (opt(&mut args, "--crf")?, opt(&mut args, "--bitrate")?) {
+                (Some(_), Some(_)) => return Err(usage("use either --crf or --bitrate")),
+                (Some(crf), None) => video.rate = RateControl::Crf(crf),
+                (None, Some(kbps)) => video.rate = RateControl::Bitrate(kbps),
+                (None, None) => {}
+            }
+            let looping = LoopSettings {
+                loop_count: opt(&mut args, "--loop")?.unwrap_or(0),
+                dither: !args.contains("--no-dither"),
+            };
+            let output: Option<PathBuf> = opt(&mut args, ["-o", "--output"])?;
+            finish(args)?;
+            export(&project, format, &video, &looping, output)
+        }
+        _ => unreachable!("commands are checked above"),
+    }
+}
+
+fn opt<T: std::str::FromStr>(
+    args: &mut pico_args::Arguments,
+    keys: impl Into<pico_args::Keys>,
+) -> anyhow::Result<Option<T>>
+where
+    T::Err: std::fmt::Display,
+{
+    args.opt_value_from_str(keys)
+        .map_err(|e| usage(e.to_string()))
+}
+
+fn finish(args: pico_args::Arguments) -> anyhow::Result<()> {
+    let rest = args.finish();
+    if let Some(arg) = rest.first() {
+        return Err(usage(format!(
+            "unexpected argument '{}'",
+            arg.to_string_lossy()
+        )));
+    }
+    Ok(())
+}
+
+fn parse_format(name: &str) -> anyhow::Result<ExportFormat> {
+    Ok(match name {
+        "png" => ExportFormat::PngSequence,
+        "gif" => ExportFormat::Gif,
+        "apng" => ExportFormat::Apng,
+        "mp4" => ExportFormat::Video(VideoCodec::H264),
+        "webm" => ExportFormat::Video(VideoCodec::Vp9),
+        "prores" | "mov" => ExportFormat::Video(VideoCodec::ProRes),
+        _ => return Err(usage(format!("unknown format '{}'", name))),
+    })
+}
+
+// ─── Project ────────────────────────────────────────────────────────────────
+
+/// Header settings of the entry file, or its header diagnostics.
+fn settings(project: &Project) -> anyhow::Result<ExportSettings> {
+    let config = dsl::parse_config(&project.source).map_err(|diags| {
+        print_diagnostics(&project.entry_path(), &diags);
+        anyhow!("{} has an invalid header", project.entry_path().display())
+    })?;
+    Ok(ExportSettings {
+        width: config.width,
+        height: config.height,
+        fps: config.fps,
+        duration: config.duration,
+    })
+}
+
+fn print_diagnostics(file: &Path, diags: &[Diagnostic]) {
+    for d in diags {
+        println!(
+            "{}:{}:{}: {}: {}",
+            file.display(),
+            d.line,
+            d.column,
+            if d.is_error() { "error" } else { "warning" },
+            d.message
+        );
+    }
+}
+
+// ─── Commands ───────────────────────────────────────────────────────────────
+
+fn check(project: &Project) -> ExitCode {
+    let mut diags = dsl::parse_config(&project.source).err().unwrap_or_default();
+    diags.extend(dsl::validate(&project.source));
+    diags.sort_by_key(|d| d.span.start);
+    diags.dedup();
+
+    print_diagnostics(&project.entry_path(), &diags);
+    if diags.is_empty() {
+        println!("{}: no problems found", project.entry_path().display());
+        return ExitCode::SUCCESS;
+    }
+    println!(
+        "{}: {} problem{}",
+        project.entry_path().display(),
+        diags.len(),
+        if diags.len() == 1 { "" } else { "s" }
+    );
+    // warnings alone do not fail the check
+    if diags.iter().any(Diagnostic::is_error) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn render(
+    project: &Project,
+    frame: Option<usize>,
+    time: Option<f32>,
+    output: Option<PathBuf>,
+) -> anyhow::Result<ExitCode> {
+    let settings = settings(project)?;
+    let (frame, time) = match (frame, time) {
+        (Some(frame), None) => (frame, settings.frame_time(frame)),
+        (None, Some(time)) => ((time * settings.fps as f32).round() as usize, time),
+        _ => return Err(usage("render needs exactly one of --frame or --time")),
+    };
+    let output = output.unwrap_or_else(|| project.dir.join("export").join(frame_file_name(frame)));
+    if let Some(dir) = output.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("could not create {}", dir.display()))?;
+    }
+
+    let scene = dsl::parse_dsl(&project.source);
+    motioner_core::renderer::render_frame(
+        &scene,
+        time,
+        settings.fps,
+        settings.width,
+        settings.height,
+    )
+    .save_with_format(&output, image::ImageFormat::Png)
+    .with_context(|| format!("could not write {}", output.display()))?;
+    println!("Rendered {:.3} s to {}", time, output.display());
+    Ok(ExitCode::SUCCESS)
+}
+
+fn export(
+    project: &Project,
+    format: ExportFormat,
+    video: &VideoSettings,
+    looping: &LoopSettings,
+    output: Option<PathBuf>,
+) -> anyhow::Result<ExitCode> {
+    let settings = settings(project)?;
+    let scene = dsl::parse_dsl(&project.source);
+    let output = output.unwrap_or_else(|| format.output_path(&project.dir));
+    let progress = ExportProgress::default();
+
+    let outcome = motioner_core::export::run(
+        format, &scene, &settings, video, looping, &output, &progress,
+    )?;
+    match outcome {
+        ExportOutcome::Finished { frames, path } => {
+            println!("Exported {} frames to {}", frames, path.display());
+            Ok(ExitCode::SUCCESS)
+        }
+        ExportOutcome::Cancelled { frames } => {
+            bail!("export stopped after {} frames", frames)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_folders_and_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("code.motioner");
+        std::fs::write(&file, "size(8, 8)\ntimeline(fps = 4, duration = 1)\n").unwrap();
+
+        let from_dir = Project::load(dir.path()).unwrap();
+        assert_eq!(from_dir.entry_path(), file);
+        assert_eq!(settings(&from_dir).unwrap().frame_count(), 4);
+        assert_eq!(check(&from_dir), ExitCode::SUCCESS);
+
+        std::fs::write(&file, "rect \"A\" { x = \"left\" }").unwrap();
+        assert_eq!(check(&Project::load(&file).unwrap()), ExitCode::FAILURE);
+        assert!(Project::load(&dir.path().join("missing.motioner")).is_err());
+    }
+
+    #[test]
+    fn parses_format_names() {
+        assert_eq!(
+            parse_format("webm").unwrap(),
+            ExportFormat::Video(VideoCodec::Vp9)
+        );
+        assert_eq!(parse_format("apng").unwrap(), ExportFormat::Apng);
+        assert!(parse_format("avi").unwrap_err().is::<UsageError>());
+    }
+}
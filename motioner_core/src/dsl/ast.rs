@@ -10,7 +10,7 @@
 pub type Point2 = (f32, f32);
 
 /// The project-level header directives (size/timeline).
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct HeaderConfig {
     pub width: u32,
     pub height: u32,
@@ -18,6 +18,18 @@ pub struct HeaderConfig {
     pub duration: f32,
 }
 
+/// Values used for any header field the source leaves out.
+impl Default for HeaderConfig {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            fps: 60,
+            duration: 5.0,
+        }
+    }
+}
+
 // ─── Easing ───────────────────────────────────────────────────────────────────
 
 // The AST no longer defines its own easing enum.  We re-use the shared
@@ -33,7 +45,6 @@ pub struct HeaderConfig {
 
 // ─── Animations ───────────────────────────────────────────────────────────────
 
-
 // ─── Shape elements ───────────────────────────────────────────────────────────
 
 // ─── Header ───────────────────────────────────────────────────────────────────
@@ -63,3 +74,105 @@ pub enum Statement {
     /// Any concrete visual shape (Circle, Text, …).
     Shape(crate::scene::Shape),
 }
+
+// ─── Syntax tree ──────────────────────────────────────────────────────────────
+
+/// Byte range into the DSL source a node was parsed from.
+pub type Span = std::ops::Range<usize>;
+
+/// A value expression exactly as written in the source, before it is lowered
+/// into scene data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    Number(f32),
+    Str(String),
+    /// A bare word such as `linear`.
+    Ident(String),
+    /// `(a, b, ...)` — a parenthesised list with at least one comma.
+    Tuple(Vec<Expr>),
+    /// `[a, b, ...]`
+    List(Vec<Expr>),
+    /// `name(arg, key = arg, ...)`
+    Call {
+        name: String,
+        args: Vec<Arg>,
+    },
+    /// Unary minus, e.g. `-0.5`.
+    Neg(Box<Expr>),
+    /// Arithmetic such as `0.5 + frame * 0.01`; `op` is one of `+ - * / % ^`.
+    Binary {
+        op: char,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    /// `start -> end`, used for time ranges such as `during = 0.0 -> 2.0`.
+    Interval(Box<Expr>, Box<Expr>),
+    /// `start..end` (end exclusive), the range a `for` loop runs over.
+    Range(Box<Expr>, Box<Expr>),
+}
+
+/// A positional (`name == None`) or named call argument.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Arg {
+    pub name: Option<String>,
+    pub value: Expr,
+}
+
+/// `key = value` inside a block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub key: String,
+    pub key_span: Span,
+    pub value: Expr,
+}
+
+/// `keyword ["label"] { ... }`, e.g. `rect "Box" { x = 0.5 }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub keyword: String,
+    pub keyword_span: Span,
+    pub label: Option<(String, Span)>,
+    pub items: Vec<Item>,
+    pub span: Span,
+}
+
+/// `let name = value`, a named constant (see [`super::scope`]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Let {
+    pub name: String,
+    pub name_span: Span,
+    pub value: Expr,
+    /// From `let` to the end of the value.
+    pub span: Span,
+}
+
+/// `for var in iterable { items }`, expanded into one copy of `items` per
+/// value of `var` when the file is lowered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct For {
+    pub var: String,
+    pub var_span: Span,
+    /// An [`ExprKind::Range`], or a list (or a binding holding one).
+    pub iterable: Expr,
+    pub items: Vec<Item>,
+    pub keyword_span: Span,
+    /// From `for` to the closing brace.
+    pub span: Span,
+}
+
+/// One entry of a block body (or of the file itself).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Field(Field),
+    Block(Block),
+    /// A bare call such as the `size(1280, 720)` header directive.
+    Call(Expr),
+    Let(Let),
+    For(For),
+}
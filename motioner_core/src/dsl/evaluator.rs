@@ -0,0 +1,997 @@
+//! Expression evaluator for the Motioner DSL.
+//!
+//! Evaluates expressions such as `sin(seconds * tau) * 0.1 + 0.5` or
+//! `frame >= 10 and name != "intro"` against a variable context.  The
+//! grammar, loosest binding first:
+//!
+//! ```text
+//! expr    := and { "or" and }
+//! and     := not { "and" not }
+//! not     := "not" not | compare
+//! compare := sum [ ("==" | "!=" | "<" | "<=" | ">" | ">=") sum ]
+//! sum     := term { ("+" | "-") term }
+//! term    := unary { ("*" | "/" | "%") unary }
+//! unary   := "-" unary | power
+//! power   := primary [ "^" unary ]              (right-associative)
+//! primary := NUMBER | STRING | "true" | "false" | IDENT
+//!          | IDENT "(" [ expr { "," expr } ] ")" | "(" expr ")"
+//! ```
+//!
+//! An expression is first [`compile`]d into a tree, which can then be
+//! evaluated any number of times (e.g. once per frame).  Results are typed
+//! [`Value`]s: arithmetic needs numbers, `<`-style comparisons need two
+//! numbers, `==`/`!=` compare values of the same type (numbers within
+//! `1e-6`), and `and`/`or`/`not` take booleans; mixing types is an error
+//! rather than a silent conversion.  `and`/`or` short-circuit.
+//!
+//! `-2 ^ 2` is `-(2 ^ 2)`, as in mathematics.  `%` is the Euclidean
+//! remainder, so `-0.25 % 1` is `0.75`, which keeps looping animations
+//! continuous for negative times.  Identifiers are looked up in the
+//! [`EvalContext`] first, then in the constants `pi` and `tau`; calls go to
+//! the built-in functions listed in [`FUNCTIONS`].  Errors carry the byte
+//! span of the token that caused them.
+//!
+//! Expressions are split by [`super::lexer`], so identifiers, numbers,
+//! strings and operators read exactly as they do in the rest of the DSL.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::lexer::{self, TokenKind};
+
+// ─── Context ─────────────────────────────────────────────────────────────────
+
+/// Generic value used by the DSL runtime (numbers, strings, lists).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f32),
+    Bool(bool),
+    Str(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Name of the type, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::Bool(_) => "boolean",
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+        }
+    }
+}
+
+/// Variables available during expression evaluation (e.g. `seconds`, `frame`).
+pub struct EvalContext {
+    pub variables: HashMap<String, Value>,
+    /// Shapes requested by runtime handlers (e.g. full `circle {}` /
+    /// `rect {}` blocks declared inside `on_time`), collected while a
+    /// handler runs. Caller should append them to the real scene after
+    /// the handler finishes.
+    pub spawned_shapes: Vec<crate::scene::Shape>,
+}
+
+impl EvalContext {
+    pub fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+            spawned_shapes: Vec::new(),
+        }
+    }
+
+    /// Builder-style helper: add a numeric variable and return `self`.
+    pub fn with_var(mut self, name: &str, val: f32) -> Self {
+        self.variables.insert(name.to_string(), Value::Number(val));
+        self
+    }
+
+    /// Set a variable to a Value (overwrites any existing variable).
+    pub fn set_var(&mut self, name: &str, val: Value) {
+        self.variables.insert(name.to_string(), val);
+    }
+
+    /// Convenience getters for common types.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.variables.get(name) {
+            Some(Value::Bool(b)) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn get_number(&self, name: &str) -> Option<f32> {
+        match self.variables.get(name) {
+            Some(Value::Number(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match self.variables.get(name) {
+            Some(Value::Str(s)) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn get_list(&self, name: &str) -> Option<&[Value]> {
+        match self.variables.get(name) {
+            Some(Value::List(v)) => Some(v.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Add a spawned shape to the evaluation context (collected by caller).
+    pub fn push_spawned_shape(&mut self, s: crate::scene::Shape) {
+        self.spawned_shapes.push(s);
+    }
+
+    /// Drain and return spawned shapes collected during evaluation.
+    pub fn take_spawned_shapes(&mut self) -> Vec<crate::scene::Shape> {
+        std::mem::take(&mut self.spawned_shapes)
+    }
+}
+
+impl Default for EvalContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ─── Errors ───────────────────────────────────────────────────────────────────
+
+/// Why an expression could not be evaluated, and where.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalError {
+    pub message: String,
+    /// Byte range of the failing token in the expression.
+    pub span: Range<usize>,
+}
+
+impl EvalError {
+    fn new(span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Move the span by `offset`, for an expression embedded in a larger
+    /// source text.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.span = self.span.start + offset..self.span.end + offset;
+        self
+    }
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.span.start)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl From<EvalError> for String {
+    fn from(err: EvalError) -> Self {
+        err.to_string()
+    }
+}
+
+// ─── Public entry point ───────────────────────────────────────────────────────
+
+/// The result type for numeric expression evaluation.
+pub type EvalResult = Result<f32, EvalError>;
+
+/// Evaluate a numeric expression against a variable context.
+pub fn evaluate(expr: &str, ctx: &EvalContext) -> EvalResult {
+    compile(expr)?.eval_number(ctx)
+}
+
+/// Evaluate an expression of any type against a variable context.
+pub fn evaluate_value(expr: &str, ctx: &EvalContext) -> Result<Value, EvalError> {
+    compile(expr)?.eval(ctx)
+}
+
+/// Parse `expr` once so it can be evaluated repeatedly.  Syntax errors,
+/// unknown functions and wrong argument counts are reported here; unknown
+/// variables only when evaluating, since they depend on the context.
+pub fn compile(expr: &str) -> Result<CompiledExpr, EvalError> {
+    let tokens = tokenize(expr)?;
+    if tokens.is_empty() {
+        return Err(EvalError::new(0..expr.len(), "Empty expression"));
+    }
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        end: expr.len(),
+    };
+    let root = parser.expr()?;
+    if let Some(tok) = parser.peek() {
+        return Err(EvalError::new(
+            tok.span.clone(),
+            format!("Unexpected {}", tok.kind.describe()),
+        ));
+    }
+    Ok(CompiledExpr { root })
+}
+
+/// A parsed expression; see [`compile`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledExpr {
+    root: Node,
+}
+
+impl CompiledExpr {
+    pub fn eval(&self, ctx: &EvalContext) -> Result<Value, EvalError> {
+        self.root.eval(ctx)
+    }
+
+    /// Evaluate and require a number.
+    pub fn eval_number(&self, ctx: &EvalContext) -> EvalResult {
+        number(self.root.eval(ctx)?, &self.root.span)
+    }
+
+    /// Evaluate and require a boolean.
+    pub fn eval_bool(&self, ctx: &EvalContext) -> Result<bool, EvalError> {
+        boolean(self.root.eval(ctx)?, &self.root.span)
+    }
+
+    /// Every variable the expression reads, with its span, in source order.
+    pub fn variables(&self) -> Vec<(&str, Range<usize>)> {
+        let mut out = Vec::new();
+        self.root.collect_variables(&mut out);
+        out
+    }
+
+    /// Replace every variable `ctx` defines by its value, so evaluating the
+    /// result only needs the variables that are left.
+    pub fn bind(mut self, ctx: &EvalContext) -> Self {
+        self.root.bind(ctx);
+        self
+    }
+}
+
+fn number(value: Value, span: &Range<usize>) -> EvalResult {
+    match value {
+        Value::Number(n) => Ok(n),
+        other => Err(EvalError::new(
+            span.clone(),
+            format!("Expected a number, found {}", other.type_name()),
+        )),
+    }
+}
+
+fn boolean(value: Value, span: &Range<usize>) -> Result<bool, EvalError> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        other => Err(EvalError::new(
+            span.clone(),
+            format!("Expected a boolean, found {}", other.type_name()),
+        )),
+    }
+}
+
+// ─── Built-ins ────────────────────────────────────────────────────────────────
+
+/// Named constants.
+pub const CONSTANTS: &[(&str, f32)] =
+    &[("pi", std::f32::consts::PI), ("tau", std::f32::consts::TAU)];
+
+/// Built-in functions and their argument names.
+pub const FUNCTIONS: &[(&str, &[&str])] = &[
+    ("sin", &["x"]),
+    ("cos", &["x"]),
+    ("tan", &["x"]),
+    ("atan2", &["y", "x"]),
+    ("sqrt", &["x"]),
+    ("abs", &["x"]),
+    ("floor", &["x"]),
+    ("ceil", &["x"]),
+    ("round", &["x"]),
+    ("min", &["a", "b"]),
+    ("max", &["a", "b"]),
+    ("clamp", &["x", "lo", "hi"]),
+    ("lerp", &["a", "b", "t"]),
+    ("smoothstep", &["edge0", "edge1", "x"]),
+    (
+        "map_range",
+        &["x", "in_min", "in_max", "out_min", "out_max"],
+    ),
+];
+
+/// Words with a meaning of their own; not usable as variable names.
+pub const KEYWORDS: &[&str] = &["and", "or", "not", "true", "false"];
+
+fn call(name: &str, a: &[f32]) -> f32 {
+    match name {
+        "sin" => a[0].sin(),
+        "cos" => a[0].cos(),
+        "tan" => a[0].tan(),
+        "atan2" => a[0].atan2(a[1]),
+        "sqrt" => a[0].sqrt(),
+        "abs" => a[0].abs(),
+        "floor" => a[0].floor(),
+        "ceil" => a[0].ceil(),
+        "round" => a[0].round(),
+        "min" => a[0].min(a[1]),
+        "max" => a[0].max(a[1]),
+        "clamp" => a[0].max(a[1]).min(a[2]),
+        "lerp" => a[0] + (a[1] - a[0]) * a[2],
+        "smoothstep" => {
+            let t = ((a[2] - a[0]) / (a[1] - a[0])).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        }
+        "map_range" => a[3] + (a[0] - a[1]) / (a[2] - a[1]) * (a[4] - a[3]),
+        _ => unreachable!("'{}' is listed in FUNCTIONS", name),
+    }
+}
+
+// ─── Tokens ───────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokKind<'a> {
+    Num(f32),
+    Str(String),
+    Ident(&'a str),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl TokKind<'_> {
+    fn describe(&self) -> String {
+        match self {
+            TokKind::Num(n) => format!("number {}", n),
+            TokKind::Str(s) => format!("string \"{}\"", s),
+            TokKind::Ident(id) => format!("'{}'", id),
+            TokKind::Op(op) => format!("'{}'", op),
+            TokKind::LParen => "'('".to_string(),
+            TokKind::RParen => "')'".to_string(),
+            TokKind::Comma => "','".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Tok<'a> {
+    kind: TokKind<'a>,
+    span: Range<usize>,
+}
+
+/// The operators of the expression grammar; the lexer's others (`->`,
+/// `..`, `=`, ...) belong to the surrounding DSL.
+const OPERATORS: &[&str] = &[
+    "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "^",
+];
+
+fn tokenize(s: &str) -> Result<Vec<Tok<'_>>, EvalError> {
+    let mut out = Vec::new();
+    for token in lexer::tokenize(s) {
+        let text = token.text(s);
+        let span = token.span.clone();
+        let kind = match token.kind {
+            TokenKind::Comment => continue,
+            TokenKind::Ident => TokKind::Ident(text),
+            TokenKind::Number { value, .. } if value.is_nan() => {
+                return Err(EvalError::new(span, format!("Invalid number '{}'", text)));
+            }
+            TokenKind::Number { unit: Some(_), .. } => {
+                return Err(EvalError::new(
+                    span,
+                    "Units cannot be used inside an expression",
+                ));
+            }
+            TokenKind::Number { value, .. } => TokKind::Num(value),
+            TokenKind::Str { terminated: false } => {
+                return Err(EvalError::new(span, "Unterminated string"));
+            }
+            TokenKind::Str { .. } => TokKind::Str(lexer::unescape(text)),
+            TokenKind::Open('(') => TokKind::LParen,
+            TokenKind::Close(')') => TokKind::RParen,
+            TokenKind::Comma => TokKind::Comma,
+            TokenKind::Op(op) if OPERATORS.contains(&op) => TokKind::Op(op),
+            TokenKind::Op(op) => {
+                return Err(EvalError::new(span, format!("Unexpected '{}'", op)));
+            }
+            _ => {
+                return Err(EvalError::new(
+                    span,
+                    format!("Unexpected character '{}'", text),
+                ));
+            }
+        };
+        out.push(Tok { kind, span });
+    }
+    Ok(out)
+}
+
+// ─── Syntax tree ──────────────────────────────────────────────────────────────
+
+#[derive(Clone, Debug, PartialEq)]
+struct Node {
+    kind: NodeKind,
+    /// The whole sub-expression.
+    span: Range<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum NodeKind {
+    Literal(Value),
+    Var(String),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    /// `op_span` is the operator token, where errors of the operation point.
+    Binary {
+        op: &'static str,
+        op_span: Range<usize>,
+        lhs: Box<Node>,
+        rhs: Box<Node>,
+    },
+    Call {
+        name: &'static str,
+        name_span: Range<usize>,
+        args: Vec<Node>,
+    },
+}
+
+impl Node {
+    fn new(kind: NodeKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+
+    fn collect_variables<'n>(&'n self, out: &mut Vec<(&'n str, Range<usize>)>) {
+        match &self.kind {
+            NodeKind::Literal(_) => {}
+            NodeKind::Var(name) => out.push((name, self.span.clone())),
+            NodeKind::Neg(arg) | NodeKind::Not(arg) => arg.collect_variables(out),
+            NodeKind::Binary { lhs, rhs, .. } => {
+                lhs.collect_variables(out);
+                rhs.collect_variables(out);
+            }
+            NodeKind::Call { args, .. } => {
+                for arg in args {
+                    arg.collect_variables(out);
+                }
+            }
+        }
+    }
+
+    fn bind(&mut self, ctx: &EvalContext) {
+        match &mut self.kind {
+            NodeKind::Literal(_) => {}
+            NodeKind::Var(name) => {
+                if let Some(v) = ctx.variables.get(name) {
+                    self.kind = NodeKind::Literal(v.clone());
+                }
+            }
+            NodeKind::Neg(arg) | NodeKind::Not(arg) => arg.bind(ctx),
+            NodeKind::Binary { lhs, rhs, .. } => {
+                lhs.bind(ctx);
+                rhs.bind(ctx);
+            }
+            NodeKind::Call { args, .. } => {
+                for arg in args {
+                    arg.bind(ctx);
+                }
+            }
+        }
+    }
+
+    fn eval(&self, ctx: &EvalContext) -> Result<Value, EvalError> {
+        let num = |node: &Node| number(node.eval(ctx)?, &node.span);
+        let flag = |node: &Node| boolean(node.eval(ctx)?, &node.span);
+        Ok(match &self.kind {
+            NodeKind::Literal(v) => v.clone(),
+            NodeKind::Var(name) => variable(ctx, name, &self.span)?,
+            NodeKind::Neg(arg) => Value::Number(-num(arg)?),
+            NodeKind::Not(arg) => Value::Bool(!flag(arg)?),
+            NodeKind::Binary {
+                op: op @ ("and" | "or"),
+                lhs,
+                rhs,
+                ..
+            } => {
+                let l = flag(lhs)?;
+                // short-circuit: `x != 0 and 1 / x > 2` never divides by zero
+                Value::Bool(match *op {
+                    "and" => l && flag(rhs)?,
+                    _ => l || flag(rhs)?,
+                })
+            }
+            NodeKind::Binary {
+                op: op @ ("==" | "!="),
+                op_span,
+                lhs,
+                rhs,
+            } => {
+                let equal = match (lhs.eval(ctx)?, rhs.eval(ctx)?) {
+                    (Value::Number(l), Value::Number(r)) => (l - r).abs() < 1e-6,
+                    (l, r) if l.type_name() == r.type_name() => l == r,
+                    (l, r) => {
+                        return Err(EvalError::new(
+                            op_span.clone(),
+                            format!("Cannot compare {} with {}", l.type_name(), r.type_name()),
+                        ))
+                    }
+                };
+                Value::Bool(equal == (*op == "=="))
+            }
+            NodeKind::Binary {
+                op,
+                op_span,
+                lhs,
+                rhs,
+            } => {
+                let (l, r) = (num(lhs)?, num(rhs)?);
+                match *op {
+                    "<" => Value::Bool(l < r),
+                    "<=" => Value::Bool(l <= r),
+                    ">" => Value::Bool(l > r),
+                    ">=" => Value::Bool(l >= r),
+                    "+" => Value::Number(l + r),
+                    "-" => Value::Number(l - r),
+                    "*" => Value::Number(l * r),
+                    "/" | "%" if r == 0.0 => {
+                        return Err(EvalError::new(op_span.clone(), "Division by zero"))
+                    }
+                    "/" => Value::Number(l / r),
+                    "%" => Value::Number(l.rem_euclid(r)),
+                    "^" => {
+                        let v = l.powf(r);
+                        if v.is_nan() {
+                            return Err(EvalError::new(
+                                op_span.clone(),
+                                format!("{} ^ {} is not a real number", l, r),
+                            ));
+                        }
+                        Value::Number(v)
+                    }
+                    _ => unreachable!("the parser only builds known operators"),
+                }
+            }
+            NodeKind::Call {
+                name,
+                name_span,
+                args,
+            } => {
+                let args = args.iter().map(num).collect::<Result<Vec<_>, _>>()?;
+                let v = call(name, &args);
+                if v.is_nan() {
+                    return Err(EvalError::new(
+                        name_span.clone(),
+                        format!("'{}' is not defined for these arguments", name),
+                    ));
+                }
+                Value::Number(v)
+            }
+        })
+    }
+}
+
+fn variable(ctx: &EvalContext, name: &str, span: &Range<usize>) -> Result<Value, EvalError> {
+    if let Some(v) = ctx.variables.get(name) {
+        return Ok(v.clone());
+    }
+    if let Some((_, v)) = CONSTANTS.iter().find(|(c, _)| *c == name) {
+        return Ok(Value::Number(*v));
+    }
+    if FUNCTIONS.iter().any(|(f, _)| *f == name) {
+        return Err(EvalError::new(
+            span.clone(),
+            format!("'{}' is a function; call it as {}(...)", name, name),
+        ));
+    }
+    Err(EvalError::new(
+        span.clone(),
+        format!("Unknown variable '{}'", name),
+    ))
+}
+
+// ─── Recursive-descent parser ─────────────────────────────────────────────────
+
+struct Parser<'t, 'a> {
+    tokens: &'t [Tok<'a>],
+    pos: usize,
+    /// Length of the expression, for errors at its end.
+    end: usize,
+}
+
+type ParseResult = Result<Node, EvalError>;
+
+impl<'a> Parser<'_, 'a> {
+    fn peek(&self) -> Option<&Tok<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Tok<'a>> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    /// Consume the next token if it is one of `ops` (operators or the
+    /// keywords `and`, `or`, `not`).
+    fn eat(&mut self, ops: &[&'static str]) -> Option<(&'static str, Range<usize>)> {
+        let tok = self.peek()?;
+        let text = match tok.kind {
+            TokKind::Op(op) => op,
+            TokKind::Ident(id) => id,
+            _ => return None,
+        };
+        let op = ops.iter().find(|op| **op == text)?;
+        let span = tok.span.clone();
+        self.pos += 1;
+        Some((op, span))
+    }
+
+    fn expect(&mut self, kind: TokKind, what: &str) -> Result<Range<usize>, EvalError> {
+        match self.next() {
+            Some(tok) if tok.kind == kind => Ok(tok.span),
+            Some(tok) => Err(EvalError::new(
+                tok.span,
+                format!("Expected {}, found {}", what, tok.kind.describe()),
+            )),
+            None => Err(EvalError::new(
+                self.end..self.end,
+                format!("Expected {}", what),
+            )),
+        }
+    }
+
+    /// One left-associative precedence level.
+    fn binary(
+        &mut self,
+        ops: &[&'static str],
+        operand: fn(&mut Self) -> ParseResult,
+    ) -> ParseResult {
+        let mut lhs = operand(self)?;
+        while let Some((op, op_span)) = self.eat(ops) {
+            let rhs = operand(self)?;
+            let span = lhs.span.start..rhs.span.end;
+            lhs = Node::new(
+                NodeKind::Binary {
+                    op,
+                    op_span,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                },
+                span,
+            );
+        }
+        Ok(lhs)
+    }
+
+    fn expr(&mut self) -> ParseResult {
+        self.binary(&["or"], Self::and)
+    }
+
+    fn and(&mut self) -> ParseResult {
+        self.binary(&["and"], Self::not)
+    }
+
+    fn not(&mut self) -> ParseResult {
+        match self.eat(&["not"]) {
+            Some((_, span)) => {
+                let arg = self.not()?;
+                let span = span.start..arg.span.end;
+                Ok(Node::new(NodeKind::Not(Box::new(arg)), span))
+            }
+            None => self.compare(),
+        }
+    }
+
+    fn compare(&mut self) -> ParseResult {
+        const COMPARISONS: &[&str] = &["==", "!=", "<", "<=", ">", ">="];
+        let lhs = self.sum()?;
+        let Some((op, op_span)) = self.eat(COMPARISONS) else {
+            return Ok(lhs);
+        };
+        let rhs = self.sum()?;
+        // comparisons do not chain: `a < b < c` would compare a boolean
+        if let Some((op, span)) = self.eat(COMPARISONS) {
+            return Err(EvalError::new(
+                span,
+                format!("Comparisons cannot be chained; use 'and' before '{}'", op),
+            ));
+        }
+        let span = lhs.span.start..rhs.span.end;
+        Ok(Node::new(
+            NodeKind::Binary {
+                op,
+                op_span,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+            span,
+        ))
+    }
+
+    fn sum(&mut self) -> ParseResult {
+        self.binary(&["+", "-"], Self::term)
+    }
+
+    fn term(&mut self) -> ParseResult {
+        self.binary(&["*", "/", "%"], Self::unary)
+    }
+
+    fn unary(&mut self) -> ParseResult {
+        match self.eat(&["-"]) {
+            Some((_, span)) => {
+                let arg = self.unary()?;
+                let span = span.start..arg.span.end;
+                Ok(Node::new(NodeKind::Neg(Box::new(arg)), span))
+            }
+            None => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> ParseResult {
+        let base = self.primary()?;
+        match self.eat(&["^"]) {
+            Some((op, op_span)) => {
+                let exp = self.unary()?;
+                let span = base.span.start..exp.span.end;
+                Ok(Node::new(
+                    NodeKind::Binary {
+                        op,
+                        op_span,
+                        lhs: Box::new(base),
+                        rhs: Box::new(exp),
+                    },
+                    span,
+                ))
+            }
+            None => Ok(base),
+        }
+    }
+
+    fn primary(&mut self) -> ParseResult {
+        let Some(tok) = self.next() else {
+            return Err(EvalError::new(
+                self.end..self.end,
+                "Unexpected end of expression",
+            ));
+        };
+        let literal = |v| Ok(Node::new(NodeKind::Literal(v), tok.span.clone()));
+        match tok.kind {
+            TokKind::Num(n) => literal(Value::Number(n)),
+            TokKind::Str(s) => literal(Value::Str(s)),
+            TokKind::Ident("true") => literal(Value::Bool(true)),
+            TokKind::Ident("false") => literal(Value::Bool(false)),
+            TokKind::LParen => {
+                let inner = self.expr()?;
+                let close = self.expect(TokKind::RParen, "')'")?;
+                Ok(Node {
+                    span: tok.span.start..close.end,
+                    ..inner
+                })
+            }
+            TokKind::Ident(name) if KEYWORDS.contains(&name) => Err(EvalError::new(
+                tok.span.clone(),
+                format!("Unexpected '{}'", name),
+            )),
+            TokKind::Ident(name)
+                if matches!(self.peek().map(|t| &t.kind), Some(TokKind::LParen)) =>
+            {
+                self.pos += 1;
+                self.call(name, tok.span.clone())
+            }
+            TokKind::Ident(name) => {
+                Ok(Node::new(NodeKind::Var(name.to_string()), tok.span.clone()))
+            }
+            other => Err(EvalError::new(
+                tok.span.clone(),
+                format!("Unexpected {}", other.describe()),
+            )),
+        }
+    }
+
+    /// Arguments of a call to `name`; the `(` is already consumed.
+    fn call(&mut self, name: &str, name_span: Range<usize>) -> ParseResult {
+        let mut args = Vec::new();
+        if !matches!(self.peek().map(|t| &t.kind), Some(TokKind::RParen)) {
+            loop {
+                args.push(self.expr()?);
+                match self.peek().map(|t| &t.kind) {
+                    Some(TokKind::Comma) => self.pos += 1,
+                    _ => break,
+                }
+            }
+        }
+        let close = self.expect(TokKind::RParen, "',' or ')'")?;
+
+        let Some((name, params)) = FUNCTIONS.iter().find(|(f, _)| *f == name) else {
+            return Err(EvalError::new(
+                name_span,
+                format!("Unknown function '{}'", name),
+            ));
+        };
+        if args.len() != params.len() {
+            return Err(EvalError::new(
+                name_span,
+                format!(
+                    "'{}' takes {} argument{} ({}), got {}",
+                    name,
+                    params.len(),
+                    if params.len() == 1 { "" } else { "s" },
+                    params.join(", "),
+                    args.len()
+                ),
+            ));
+        }
+        let span = name_span.start..close.end;
+        Ok(Node::new(
+            NodeKind::Call {
+                name,
+                name_span,
+                args,
+            },
+            span,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> EvalContext {
+        let mut ctx = EvalContext::new().with_var("seconds", 2.0);
+        ctx.set_var("name", Value::Str("ball".into()));
+        ctx.set_var("visible", Value::Bool(true));
+        ctx
+    }
+
+    fn eval(expr: &str) -> f32 {
+        evaluate(expr, &ctx()).unwrap_or_else(|e| panic!("{}: {}", expr, e))
+    }
+
+    fn truth(expr: &str) -> bool {
+        match evaluate_value(expr, &ctx()) {
+            Ok(Value::Bool(b)) => b,
+            other => panic!("{}: {:?}", expr, other),
+        }
+    }
+
+    fn error(expr: &str) -> (String, Range<usize>) {
+        let err = evaluate_value(expr, &ctx()).unwrap_err();
+        (err.message, err.span)
+    }
+
+    #[test]
+    fn precedence_unary_and_power() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("-0.5"), -0.5);
+        assert_eq!(eval("2 * -seconds"), -4.0);
+        assert_eq!(eval("--1 + 1"), 2.0);
+        assert_eq!(eval("-2 ^ 2"), -4.0);
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(eval("2 ^ -1"), 0.5);
+        assert_eq!(eval("7 % 3"), 1.0);
+        assert_eq!(eval("-0.25 % 1"), 0.75);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+    }
+
+    #[test]
+    fn functions_and_constants() {
+        assert!((eval("sin(pi / 2)") - 1.0).abs() < 1e-6);
+        assert!((eval("cos(tau)") - 1.0).abs() < 1e-6);
+        assert!((eval("atan2(1, 1)") - std::f32::consts::FRAC_PI_4).abs() < 1e-6);
+        assert_eq!(eval("sqrt(16) + abs(-2)"), 6.0);
+        assert_eq!(eval("floor(1.7) + ceil(1.2) + round(2.5)"), 6.0);
+        assert_eq!(eval("min(seconds, 1) + max(seconds, 3)"), 4.0);
+        assert_eq!(eval("clamp(5, 0, 1)"), 1.0);
+        assert_eq!(eval("lerp(10, 20, 0.25)"), 12.5);
+        assert_eq!(eval("smoothstep(0, 1, 0.5)"), 0.5);
+        assert_eq!(eval("smoothstep(0, 1, 2)"), 1.0);
+        assert_eq!(eval("map_range(seconds, 0, 4, 100, 200)"), 150.0);
+    }
+
+    #[test]
+    fn comparisons_and_logic() {
+        assert!(truth("seconds >= 2 and seconds < 3"));
+        assert!(truth("seconds < 1 or name == \"ball\""));
+        assert!(truth("not seconds > 5"));
+        assert!(truth("1 + 1 == 2 and not false"));
+        assert!(truth("0.1 + 0.2 == 0.3"));
+        assert!(truth("name != \"box\" and visible"));
+        // `and` binds tighter than `or`
+        assert!(truth("true or false and false"));
+        assert!(!truth("(true or false) and false"));
+        // the right side is skipped once the result is known
+        assert!(!truth("seconds == 0 and 1 / (seconds - 2) > 0"));
+        assert!(truth("seconds > 0 or missing"));
+    }
+
+    #[test]
+    fn type_errors_are_reported() {
+        assert_eq!(
+            error("seconds < name"),
+            ("Expected a number, found string".into(), 10..14)
+        );
+        assert_eq!(
+            error("seconds == name"),
+            ("Cannot compare number with string".into(), 8..10)
+        );
+        assert_eq!(
+            error("seconds and true"),
+            ("Expected a boolean, found number".into(), 0..7)
+        );
+        assert_eq!(
+            error("1 < 2 < 3"),
+            (
+                "Comparisons cannot be chained; use 'and' before '<'".into(),
+                6..7
+            )
+        );
+        let err = evaluate("seconds > 1", &ctx()).unwrap_err();
+        assert_eq!(
+            (err.message.as_str(), err.span),
+            ("Expected a number, found boolean", 0..11)
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_failing_token() {
+        assert_eq!(error("1 + $"), ("Unexpected character '$'".into(), 4..5));
+        assert_eq!(
+            error("1 + speed"),
+            ("Unknown variable 'speed'".into(), 4..9)
+        );
+        assert_eq!(
+            error("wobble(1)"),
+            ("Unknown function 'wobble'".into(), 0..6)
+        );
+        assert_eq!(
+            error("clamp(1, 2)"),
+            ("'clamp' takes 3 arguments (x, lo, hi), got 2".into(), 0..5)
+        );
+        assert_eq!(error("(1 + 2"), ("Expected ')'".into(), 6..6));
+        assert_eq!(error("1 2"), ("Unexpected number 2".into(), 2..3));
+        assert_eq!(error("4 / (2 - 2)"), ("Division by zero".into(), 2..3));
+        assert_eq!(error("\"open"), ("Unterminated string".into(), 0..5));
+        assert_eq!(error("sqrt(-1)").1, 0..4);
+        assert_eq!(error("1 +").1, 3..3);
+        assert_eq!(error("  ").0, "Empty expression");
+
+        let err = evaluate("x", &EvalContext::new()).unwrap_err().offset(10);
+        assert_eq!(err.span, 10..11);
+        assert_eq!(err.to_string(), "Unknown variable 'x' at offset 10");
+    }
+
+    #[test]
+    fn tokens_read_as_in_the_dsl() {
+        let ctx = ctx().with_var("año", 3.0).with_var("größe", 0.5);
+        assert_eq!(evaluate("año * größe", &ctx), Ok(1.5));
+        assert_eq!(eval("1 + // half\n.5"), 1.5);
+        assert_eq!(
+            evaluate_value("\"a\\tb\\\"c\\\\\"", &ctx),
+            Ok(Value::Str("a\tb\"c\\".into()))
+        );
+        // the DSL has no unary plus, so neither do expressions
+        assert_eq!(error("+1"), ("Unexpected '+'".into(), 0..1));
+        assert_eq!(error("seconds -> 2"), ("Unexpected '->'".into(), 8..10));
+        assert_eq!(
+            error("2s"),
+            ("Units cannot be used inside an expression".into(), 0..2)
+        );
+    }
+
+    #[test]
+    fn compiled_expressions_list_their_variables() {
+        let expr = compile("lerp(a, b, seconds) < pi and not done").unwrap();
+        let names: Vec<_> = expr.variables().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, ["a", "b", "seconds", "pi", "done"]);
+        let ctx = ctx().with_var("a", 0.0).with_var("b", 1.0);
+        assert!(expr.eval(&ctx).is_err());
+
+        let bound = expr.bind(&EvalContext::new().with_var("a", 0.0).with_var("b", 4.0));
+        let names: Vec<_> = bound.variables().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, ["seconds", "pi", "done"]);
+        let mut ctx = EvalContext::new().with_var("seconds", 0.5);
+        ctx.set_var("done", Value::Bool(false));
+        assert_eq!(bound.eval(&ctx), Ok(Value::Bool(true)));
+    }
+}
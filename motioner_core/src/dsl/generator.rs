@@ -0,0 +1,223 @@
+/// DSL code generator: converts a scene back into DSL source text.
+use super::ast::{ExprKind, HeaderConfig, Item};
+use crate::scene::Shape;
+
+/// Generate DSL directly from `Shape` objects.
+pub fn generate_dsl_from_elements(
+    elements: &[Shape],
+    width: u32,
+    height: u32,
+    fps: u32,
+    duration: f32,
+) -> String {
+    let mut out = String::new();
+
+    // Header
+    out.push_str(&format!(
+        "{}\n{}\n\n",
+        size_directive(width, height),
+        timeline_directive(fps, duration)
+    ));
+
+    // Shape definitions
+    for shape in elements {
+        out.push_str(&shape.to_dsl(""));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn size_directive(width: u32, height: u32) -> String {
+    format!("size({}, {})", width, height)
+}
+
+fn timeline_directive(fps: u32, duration: f32) -> String {
+    // two decimals unless that would change the value
+    let mut seconds = format!("{:.2}", duration);
+    if seconds.parse::<f32>() != Ok(duration) {
+        seconds = duration.to_string();
+    }
+    format!("timeline(fps = {}, duration = {})", fps, seconds)
+}
+
+/// Rewrite the `size(...)`/`timeline(...)` header of `src` to `config`,
+/// leaving the rest of the source untouched.
+///
+/// A directive that already has the requested values is kept exactly as
+/// written.  A missing one is inserted next to the other, `size` first, or
+/// both at the top of the file when neither is there.
+pub fn rewrite_header(src: &str, config: &HeaderConfig) -> String {
+    let current = super::parser::parse_config(src).ok();
+    let (items, _) = super::parser::parse_items(src);
+    let mut size_span = None;
+    let mut timeline_span = None;
+    for item in &items {
+        let Item::Call(call) = item else { continue };
+        let ExprKind::Call { name, .. } = &call.kind else {
+            continue;
+        };
+        let slot = match name.as_str() {
+            "size" => &mut size_span,
+            "timeline" => &mut timeline_span,
+            _ => continue,
+        };
+        slot.get_or_insert(call.span.clone());
+    }
+
+    let size_changed = current
+        .as_ref()
+        .is_none_or(|c| (c.width, c.height) != (config.width, config.height));
+    let timeline_changed = current
+        .as_ref()
+        .is_none_or(|c| (c.fps, c.duration) != (config.fps, config.duration));
+
+    let size = size_directive(config.width, config.height);
+    let timeline = timeline_directive(config.fps, config.duration);
+    let mut prefix = String::new();
+    let mut edits = Vec::new();
+    match (size_span, timeline_span) {
+        (None, None) => {
+            prefix = format!("{}\n{}\n", size, timeline);
+            if !src.is_empty() {
+                prefix.push('\n');
+            }
+        }
+        // a missing directive goes next to the one that is there, size first
+        (Some(span), None) => {
+            edits.push((span.end..span.end, format!("\n{}", timeline)));
+            if size_changed {
+                edits.push((span, size));
+            }
+        }
+        (None, Some(span)) => {
+            edits.push((span.start..span.start, format!("{}\n", size)));
+            if timeline_changed {
+                edits.push((span, timeline));
+            }
+        }
+        (Some(size_span), Some(timeline_span)) => {
+            if size_changed {
+                edits.push((size_span, size));
+            }
+            if timeline_changed {
+                edits.push((timeline_span, timeline));
+            }
+        }
+    }
+
+    let mut out = src.to_string();
+    edits.sort_by_key(|(span, _)| std::cmp::Reverse((span.start, span.end)));
+    for (span, text) in edits {
+        out.replace_range(span, &text);
+    }
+    out.insert_str(0, &prefix);
+    out
+}
+
+/// Convert leading groups of 4 spaces into tab characters for every line.
+pub fn normalize_tabs(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+
+    for segment in src.split_inclusive('\n') {
+        if segment == "\n" {
+            out.push('\n');
+            continue;
+        }
+
+        let has_newline = segment.ends_with('\n');
+        let line = if has_newline {
+            &segment[..segment.len() - 1]
+        } else {
+            segment
+        };
+
+        let mut i = 0usize;
+        let bytes = line.as_bytes();
+        let mut leading = String::new();
+        while i < bytes.len() {
+            let c = bytes[i] as char;
+            if c == '\t' {
+                leading.push('\t');
+                i += 1;
+            } else if c == ' ' {
+                let mut count = 0usize;
+                while i + count < bytes.len() && bytes[i + count] == b' ' {
+                    count += 1;
+                }
+                let tabs = count / 4;
+                let rem = count % 4;
+                for _ in 0..tabs {
+                    leading.push('\t');
+                }
+                for _ in 0..rem {
+                    leading.push(' ');
+                }
+                i += count;
+            } else {
+                break;
+            }
+        }
+        out.push_str(&leading);
+        out.push_str(&line[i..]);
+        if has_newline {
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(width: u32, height: u32, fps: u32, duration: f32) -> HeaderConfig {
+        HeaderConfig {
+            width,
+            height,
+            fps,
+            duration,
+        }
+    }
+
+    #[test]
+    fn rewrites_only_changed_directives() {
+        let src =
+            "// intro\nsize(width = 640, height = 480)\ntimeline(30, 2)\n\nrect \"A\" { x = 1 }\n";
+        let out = rewrite_header(src, &config(640, 480, 24, 2.5));
+        assert_eq!(
+            out,
+            "// intro\nsize(width = 640, height = 480)\ntimeline(fps = 24, duration = 2.50)\n\nrect \"A\" { x = 1 }\n"
+        );
+        assert_eq!(rewrite_header(&out, &config(640, 480, 24, 2.5)), out);
+
+        let out = rewrite_header(&out, &config(1920, 1080, 24, 0.125));
+        assert!(
+            out.starts_with("// intro\nsize(1920, 1080)\ntimeline(fps = 24, duration = 0.125)\n")
+        );
+        assert_eq!(
+            super::super::parse_config(&out).unwrap(),
+            config(1920, 1080, 24, 0.125)
+        );
+    }
+
+    #[test]
+    fn inserts_a_missing_header() {
+        let out = rewrite_header("rect \"A\" {}\n", &config(8, 8, 4, 1.0));
+        assert_eq!(
+            out,
+            "size(8, 8)\ntimeline(fps = 4, duration = 1.00)\n\nrect \"A\" {}\n"
+        );
+        let out = rewrite_header("size(8, 8)\n", &config(8, 8, 4, 1.0));
+        assert_eq!(out, "size(8, 8)\ntimeline(fps = 4, duration = 1.00)\n");
+        let out = rewrite_header(
+            "// intro\ntimeline(4, 1)\nrect \"A\" {}\n",
+            &config(16, 9, 4, 1.0),
+        );
+        assert_eq!(
+            out,
+            "// intro\nsize(16, 9)\ntimeline(4, 1)\nrect \"A\" {}\n"
+        );
+    }
+}
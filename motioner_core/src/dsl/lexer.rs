@@ -0,0 +1,391 @@
+//! Token-level lexer for the Motioner DSL.
+//!
+//! Every consumer that needs to understand DSL text — the parser, the code
+//! panel's highlighter, bracket matching and completion — works from the
+//! token stream produced here instead of scanning characters on its own.
+//! Tokens carry byte spans into the original source; whitespace is not
+//! tokenized, so the gaps between spans are always whitespace.
+
+use std::ops::Range;
+
+/// Unit suffix written directly after a number, e.g. `500ms` or `50%`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Seconds,
+    Millis,
+    Degrees,
+    Percent,
+}
+
+impl Unit {
+    fn from_suffix(s: &str) -> Option<Self> {
+        match s {
+            "s" => Some(Unit::Seconds),
+            "ms" => Some(Unit::Millis),
+            "deg" => Some(Unit::Degrees),
+            "%" => Some(Unit::Percent),
+            _ => None,
+        }
+    }
+
+    /// Convert a value written in this unit to the DSL's base unit
+    /// (seconds for time, fractions for percentages, degrees for angles).
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Unit::Seconds | Unit::Degrees => value,
+            Unit::Millis => value / 1000.0,
+            Unit::Percent => value / 100.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenKind {
+    Ident,
+    /// A numeric literal; `value` is as written, before `unit` is applied.
+    Number {
+        value: f32,
+        unit: Option<Unit>,
+    },
+    /// A `"..."` literal, quotes included in the span.
+    Str {
+        terminated: bool,
+    },
+    /// An unquoted `#rrggbb` / `#rrggbbaa` literal.
+    HexColor,
+    /// `// ...` up to (not including) the newline.
+    Comment,
+    /// Operators, longest match first: `->`, `..`, `==`, `!=`, `<=`, `>=`,
+    /// then single characters such as `=`, `+`, `-`, `*`, `/`, `%`, `^`.
+    Op(&'static str),
+    Comma,
+    /// `(`, `[` or `{`
+    Open(char),
+    /// `)`, `]` or `}`
+    Close(char),
+    /// A character that cannot start any token.
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+impl Token {
+    /// The source text covered by this token.
+    pub fn text<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.span.clone()]
+    }
+}
+
+const OPERATORS: &[&str] = &[
+    "->", "..", "==", "!=", "<=", ">=", "=", "+", "-", "*", "/", "%", "^", "<", ">", "!", ":", ".",
+];
+
+/// Split `src` into tokens.  Never fails: anything unrecognised becomes a
+/// [`TokenKind::Unknown`] token so callers can report it where they like.
+pub fn tokenize(src: &str) -> Vec<Token> {
+    let mut out = Vec::new();
+    let bytes = src.as_bytes();
+    let mut i = 0usize;
+
+    while i < src.len() {
+        let c = src[i..].chars().next().unwrap_or('\0');
+        if c.is_whitespace() {
+            i += c.len_utf8();
+            continue;
+        }
+
+        let start = i;
+        let kind = if src[i..].starts_with("//") {
+            i = src[i..].find('\n').map(|n| i + n).unwrap_or(src.len());
+            TokenKind::Comment
+        } else if c.is_ascii_digit()
+            || (c == '.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        {
+            i = scan_number(src, i);
+            let value = src[start..i].parse::<f32>().unwrap_or(f32::NAN);
+            let unit = scan_unit(src, i).map(|(unit, end)| {
+                i = end;
+                unit
+            });
+            TokenKind::Number { value, unit }
+        } else if c.is_alphabetic() || c == '_' {
+            i = scan_ident(src, i);
+            TokenKind::Ident
+        } else if c == '"' {
+            i += 1;
+            let mut terminated = false;
+            while let Some(ch) = src[i..].chars().next() {
+                if ch == '\n' {
+                    break;
+                }
+                i += ch.len_utf8();
+                if ch == '"' {
+                    terminated = true;
+                    break;
+                }
+                if ch == '\\' {
+                    if let Some(esc) = src[i..].chars().next().filter(|&e| e != '\n') {
+                        i += esc.len_utf8();
+                    }
+                }
+            }
+            TokenKind::Str { terminated }
+        } else if c == '#' {
+            i += 1;
+            while i < src.len() && bytes[i].is_ascii_hexdigit() {
+                i += 1;
+            }
+            if matches!(i - start, 7 | 9) {
+                TokenKind::HexColor
+            } else {
+                TokenKind::Unknown
+            }
+        } else if c == ',' {
+            i += 1;
+            TokenKind::Comma
+        } else if "([{".contains(c) {
+            i += 1;
+            TokenKind::Open(c)
+        } else if ")]}".contains(c) {
+            i += 1;
+            TokenKind::Close(c)
+        } else if let Some(op) = OPERATORS.iter().find(|op| src[i..].starts_with(**op)) {
+            i += op.len();
+            TokenKind::Op(op)
+        } else {
+            i += c.len_utf8();
+            TokenKind::Unknown
+        };
+
+        out.push(Token {
+            kind,
+            span: start..i,
+        });
+    }
+
+    out
+}
+
+fn scan_number(src: &str, mut i: usize) -> usize {
+    let bytes = src.as_bytes();
+    let mut seen_dot = false;
+    while i < src.len() {
+        match bytes[i] {
+            b'0'..=b'9' => i += 1,
+            // a second dot, or `..` (range operator), ends the number
+            b'.' if !seen_dot && bytes.get(i + 1) != Some(&b'.') => {
+                seen_dot = true;
+                i += 1;
+            }
+            _ => break,
+        }
+    }
+    i
+}
+
+fn scan_ident(src: &str, mut i: usize) -> usize {
+    while let Some(ch) = src[i..].chars().next() {
+        if !(ch.is_alphanumeric() || ch == '_') {
+            break;
+        }
+        i += ch.len_utf8();
+    }
+    i
+}
+
+/// A unit suffix directly after a number.  Letters only count when they
+/// form a whole known unit (`2s`, not `2sec`); `%` only counts when it is
+/// not followed by an operand, so `10%3` still lexes as a modulo.
+fn scan_unit(src: &str, i: usize) -> Option<(Unit, usize)> {
+    let rest = &src[i..];
+    if let Some(after) = rest.strip_prefix('%') {
+        let next = after.chars().next();
+        if next.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '(' || c == '.') {
+            return None;
+        }
+        return Some((Unit::Percent, i + 1));
+    }
+    if rest.starts_with(|c: char| c.is_alphabetic()) {
+        let end = scan_ident(src, i);
+        return Unit::from_suffix(&src[i..end]).map(|u| (u, end));
+    }
+    None
+}
+
+/// Decode the contents of a string token's text (quotes included).
+pub fn unescape(literal: &str) -> String {
+    let inner = literal.strip_prefix('"').unwrap_or(literal);
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Index of the token whose span contains `offset`, or that ends exactly at
+/// it (so a cursor placed right after a word still finds that word).
+pub fn token_at(tokens: &[Token], offset: usize) -> Option<usize> {
+    let idx = tokens.partition_point(|t| t.span.end < offset);
+    tokens
+        .get(idx)
+        .filter(|t| t.span.start <= offset)
+        .map(|_| idx)
+}
+
+/// Index of the delimiter that closes (or opens) the one at `index`.
+/// Delimiters of different kinds do not nest into each other: a stray `]`
+/// inside `( ... )` is ignored.
+pub fn matching_delim(tokens: &[Token], index: usize) -> Option<usize> {
+    let start = tokens.get(index)?.kind;
+    let (deeper, shallower) = match start {
+        TokenKind::Open(c) => (start, TokenKind::Close(closing(c))),
+        TokenKind::Close(c) => (start, TokenKind::Open(opening(c))),
+        _ => return None,
+    };
+    let indices: Box<dyn Iterator<Item = usize>> = match start {
+        TokenKind::Open(_) => Box::new(index..tokens.len()),
+        _ => Box::new((0..=index).rev()),
+    };
+
+    let mut depth = 0usize;
+    for i in indices {
+        if tokens[i].kind == deeper {
+            depth += 1;
+        } else if tokens[i].kind == shallower {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+fn closing(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+fn opening(close: char) -> char {
+    match close {
+        ')' => '(',
+        ']' => '[',
+        _ => '{',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(src: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(src)
+            .into_iter()
+            .map(|t| (t.kind, &src[t.span]))
+            .collect()
+    }
+
+    #[test]
+    fn tokens_carry_kinds_and_spans() {
+        let src = "rect \"B\\\"ox\" { x = -0.5, fill = #ff3366 } // done";
+        assert_eq!(
+            kinds(src),
+            vec![
+                (TokenKind::Ident, "rect"),
+                (TokenKind::Str { terminated: true }, "\"B\\\"ox\""),
+                (TokenKind::Open('{'), "{"),
+                (TokenKind::Ident, "x"),
+                (TokenKind::Op("="), "="),
+                (TokenKind::Op("-"), "-"),
+                (
+                    TokenKind::Number {
+                        value: 0.5,
+                        unit: None
+                    },
+                    "0.5"
+                ),
+                (TokenKind::Comma, ","),
+                (TokenKind::Ident, "fill"),
+                (TokenKind::Op("="), "="),
+                (TokenKind::HexColor, "#ff3366"),
+                (TokenKind::Close('}'), "}"),
+                (TokenKind::Comment, "// done"),
+            ]
+        );
+        assert_eq!(unescape("\"B\\\"ox\""), "B\"ox");
+    }
+
+    #[test]
+    fn numbers_units_arrows_and_ranges() {
+        let src = "during = 0 -> 500ms, 2s, 50%, 10%3, 0..12, 2sec";
+        let got: Vec<_> = kinds(src)
+            .into_iter()
+            .filter(|(k, _)| *k != TokenKind::Comma)
+            .collect();
+        let number = |value, unit| TokenKind::Number { value, unit };
+        assert_eq!(
+            got,
+            vec![
+                (TokenKind::Ident, "during"),
+                (TokenKind::Op("="), "="),
+                (number(0.0, None), "0"),
+                (TokenKind::Op("->"), "->"),
+                (number(500.0, Some(Unit::Millis)), "500ms"),
+                (number(2.0, Some(Unit::Seconds)), "2s"),
+                (number(50.0, Some(Unit::Percent)), "50%"),
+                (number(10.0, None), "10"),
+                (TokenKind::Op("%"), "%"),
+                (number(3.0, None), "3"),
+                (number(0.0, None), "0"),
+                (TokenKind::Op(".."), ".."),
+                (number(12.0, None), "12"),
+                (number(2.0, None), "2"),
+                (TokenKind::Ident, "sec"),
+            ]
+        );
+        assert_eq!(Unit::Millis.apply(500.0), 0.5);
+    }
+
+    #[test]
+    fn unterminated_strings_stop_at_newline() {
+        let src = "\"abc\nrect";
+        assert_eq!(
+            kinds(src),
+            vec![
+                (TokenKind::Str { terminated: false }, "\"abc"),
+                (TokenKind::Ident, "rect"),
+            ]
+        );
+    }
+
+    #[test]
+    fn delimiters_match_in_both_directions() {
+        let src = "f(a, [b], \")\") { }";
+        let tokens = tokenize(src);
+        let open = tokens.iter().position(|t| t.text(src) == "(").unwrap();
+        let close = matching_delim(&tokens, open).unwrap();
+        assert_eq!(tokens[close].span.start, 13);
+        assert_eq!(matching_delim(&tokens, close), Some(open));
+        assert_eq!(token_at(&tokens, 1), Some(0));
+        assert_eq!(token_at(&tokens, 2), Some(1));
+    }
+}
@@ -1,16 +1,17 @@
 /// Motioner DSL - public module facade.
 pub mod ast;
+pub mod evaluator;
 pub mod generator;
-// lexer is currently unused (extract_balanced was only required by the
-// parser); drop the module until the parser is restored.
-// pub mod lexer;
+pub mod lexer;
 pub mod parser;
+pub mod runtime;
+pub mod scope;
 pub mod utils;
 pub mod validator;
 
 // --- Re-exports ---
 pub use parser::parse_config;
-pub use validator::{validate, Diagnostic};
+pub use validator::{validate, Diagnostic, Severity};
 
 use crate::scene::Shape;
 
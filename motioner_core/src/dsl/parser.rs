@@ -0,0 +1,2160 @@
+//! Recursive-descent parser for the Motioner DSL.
+//!
+//! Parsing happens in two stages.  The source is first read into a generic
+//! syntax tree of blocks, fields and calls (see [`super::ast::Item`]); that
+//! tree is then lowered into scene data.  Keeping the two apart lets the
+//! validator and the editor work with exact source spans while the rest of
+//! the application only ever sees plain [`Shape`]s.
+//!
+//! The grammar accepted by the first stage is deliberately small:
+//!
+//! ```text
+//! items   := { item [","] }
+//! item    := IDENT "=" expr                  (field)
+//!          | IDENT [STRING] "{" items "}"    (block)
+//!          | IDENT "(" args ")"              (call)
+//!          | "let" IDENT "=" expr            (binding)
+//!          | "for" IDENT "in" iter "{" items "}"   (loop)
+//! iter    := sum ".." sum | expr
+//! expr    := sum [ "->" sum ]
+//! sum     := term { ("+" | "-") term }
+//! term    := unary { ("*" | "/" | "%") unary }
+//! unary   := "-" unary | power
+//! power   := primary [ "^" unary ]
+//! primary := NUMBER | STRING | IDENT [ "(" args ")" ]
+//!          | "(" expr { "," expr } ")" | "[" [ expr { "," expr } ] "]"
+//! ```
+//!
+//! Arithmetic uses the precedence of [`super::evaluator`], which evaluates
+//! the numeric shape properties that are not plain literals.
+
+use super::ast::{
+    Arg, Block, Expr, ExprKind, Field, For, HeaderConfig, Item, Let, Span, Statement,
+};
+use super::evaluator::{self, CompiledExpr, EvalContext, Value};
+use super::lexer::{self, TokenKind};
+use super::scope::Scope;
+use super::validator::Diagnostic;
+use crate::scene::{Animation, EaseMode, Easing, Property, PropertyExpr, Shape, StepJump};
+
+/// Parse the supplied DSL source into top-level statements.
+///
+/// Syntax errors are skipped over so that a typo in one element does not
+/// hide the rest of the scene; use [`parse_with_diagnostics`] to see them.
+pub fn parse(src: &str) -> Vec<Statement> {
+    parse_with_diagnostics(src).0
+}
+
+/// Parse the supplied DSL source and also return every syntax error found.
+pub fn parse_with_diagnostics(src: &str) -> (Vec<Statement>, Vec<Diagnostic>) {
+    let (items, mut diagnostics) = parse_items(src);
+    let statements = lower_items(src, &items, &mut diagnostics);
+    (statements, diagnostics)
+}
+
+/// Run only the first parsing stage and return the raw syntax tree.
+pub fn parse_items(src: &str) -> (Vec<Item>, Vec<Diagnostic>) {
+    let mut parser = Parser::new(src);
+    let items = parser.items(None);
+    (items, parser.diagnostics)
+}
+
+/// Parse the project header: `size(width, height)` and
+/// `timeline(fps = N, duration = D)`.
+///
+/// Arguments may be positional or named.  Fields that are not written,
+/// or a whole missing header, fall back to [`HeaderConfig::default`].
+pub fn parse_config(src: &str) -> Result<HeaderConfig, Vec<Diagnostic>> {
+    let (config, mut diags) = read_header(src);
+    diags.retain(Diagnostic::is_error);
+    if diags.is_empty() {
+        Ok(config)
+    } else {
+        Err(diags)
+    }
+}
+
+/// [`parse_config`] along with its warnings, in source order: a file
+/// without a header still works, but most likely lost it by accident.
+pub(crate) fn read_header(src: &str) -> (HeaderConfig, Vec<Diagnostic>) {
+    let (items, _) = parse_items(src);
+    let mut config = HeaderConfig::default();
+    let mut diags = Vec::new();
+    let mut seen_size: Option<Span> = None;
+    let mut seen_timeline: Option<Span> = None;
+
+    for item in &items {
+        let Item::Call(call) = item else { continue };
+        let ExprKind::Call { name, args } = &call.kind else {
+            continue;
+        };
+        let seen = match name.as_str() {
+            "size" => &mut seen_size,
+            "timeline" => &mut seen_timeline,
+            _ => continue,
+        };
+        if seen.is_some() {
+            diags.push(Diagnostic::at(
+                src,
+                call.span.clone(),
+                format!("Duplicate '{}' directive", name),
+            ));
+            continue;
+        }
+        *seen = Some(call.span.clone());
+
+        if name == "size" {
+            let [width, height] = bind_args(src, name, args, ["width", "height"], &mut diags);
+            if let Some(v) = header_int(src, width, 1, 16384, &mut diags) {
+                config.width = v;
+            }
+            if let Some(v) = header_int(src, height, 1, 16384, &mut diags) {
+                config.height = v;
+            }
+        } else {
+            let [fps, duration] = bind_args(src, name, args, ["fps", "duration"], &mut diags);
+            if let Some(v) = header_int(src, fps, 1, 240, &mut diags) {
+                config.fps = v;
+            }
+            if let Some(expr) = duration {
+                match expr_number(expr) {
+                    Some(v) if v.is_finite() && v > 0.0 => config.duration = v,
+                    Some(_) => diags.push(Diagnostic::at(
+                        src,
+                        expr.span.clone(),
+                        "Duration must be greater than zero",
+                    )),
+                    None => diags.push(Diagnostic::at(
+                        src,
+                        expr.span.clone(),
+                        "Expected a number of seconds",
+                    )),
+                }
+            }
+        }
+    }
+
+    if seen_size.is_none() && seen_timeline.is_none() {
+        diags.push(Diagnostic::warning(
+            src,
+            0..0,
+            "Missing project header: size(...) and timeline(...) use the defaults",
+        ));
+    }
+
+    diags.sort_by_key(|d| d.span.start);
+    (config, diags)
+}
+
+// ─── Tokens ───────────────────────────────────────────────────────────────────
+
+/// The parser's view of a [`lexer::Token`]: comments are dropped, literal
+/// values are decoded and units applied.
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Ident(String),
+    Num(f32),
+    Str(String),
+    Sym(char),
+    Op(&'static str),
+    Eof,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    tok: Tok,
+    span: Span,
+}
+
+fn tokenize(src: &str, diagnostics: &mut Vec<Diagnostic>) -> Vec<Token> {
+    let mut out = Vec::new();
+    for token in lexer::tokenize(src) {
+        let text = token.text(src);
+        let span = token.span.clone();
+        let tok = match token.kind {
+            TokenKind::Comment => continue,
+            TokenKind::Ident => Tok::Ident(text.to_string()),
+            TokenKind::Number { value, unit } => {
+                if value.is_nan() {
+                    diagnostics.push(Diagnostic::at(
+                        src,
+                        span,
+                        format!("Invalid number '{}'", text),
+                    ));
+                    continue;
+                }
+                Tok::Num(unit.map_or(value, |u| u.apply(value)))
+            }
+            TokenKind::Str { terminated } => {
+                if !terminated {
+                    diagnostics.push(Diagnostic::at(src, span.clone(), "Unterminated string"));
+                }
+                Tok::Str(lexer::unescape(text))
+            }
+            // an unquoted `#rrggbb` is read exactly like its quoted form
+            TokenKind::HexColor => Tok::Str(text.to_string()),
+            TokenKind::Comma => Tok::Sym(','),
+            TokenKind::Open(c) | TokenKind::Close(c) => Tok::Sym(c),
+            TokenKind::Op("=") => Tok::Sym('='),
+            TokenKind::Op("-") => Tok::Sym('-'),
+            TokenKind::Op(op) => Tok::Op(op),
+            TokenKind::Unknown => {
+                diagnostics.push(Diagnostic::at(
+                    src,
+                    span,
+                    format!("Unexpected character '{}'", text),
+                ));
+                continue;
+            }
+        };
+        out.push(Token { tok, span });
+    }
+
+    out.push(Token {
+        tok: Tok::Eof,
+        span: src.len()..src.len(),
+    });
+    out
+}
+
+// ─── Syntax parser ────────────────────────────────────────────────────────────
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        let mut diagnostics = Vec::new();
+        let tokens = tokenize(src, &mut diagnostics);
+        Self {
+            src,
+            tokens,
+            pos: 0,
+            diagnostics,
+        }
+    }
+
+    fn peek(&self) -> &Tok {
+        &self.tokens[self.pos].tok
+    }
+
+    fn peek_at(&self, n: usize) -> &Tok {
+        let idx = (self.pos + n).min(self.tokens.len() - 1);
+        &self.tokens[idx].tok
+    }
+
+    fn span(&self) -> Span {
+        self.tokens[self.pos].span.clone()
+    }
+
+    /// End offset of the most recently consumed token.
+    fn prev_end(&self) -> usize {
+        if self.pos == 0 {
+            0
+        } else {
+            self.tokens[self.pos - 1].span.end
+        }
+    }
+
+    fn bump(&mut self) -> Token {
+        let tok = self.tokens[self.pos].clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn at_sym(&self, c: char) -> bool {
+        *self.peek() == Tok::Sym(c)
+    }
+
+    fn eat_sym(&mut self, c: char) -> bool {
+        if self.at_sym(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&mut self, span: Span, message: impl Into<String>) {
+        self.diagnostics
+            .push(Diagnostic::at(self.src, span, message));
+    }
+
+    fn expect_sym(&mut self, c: char) -> bool {
+        if self.eat_sym(c) {
+            return true;
+        }
+        let found = describe(self.peek());
+        self.error(self.span(), format!("Expected '{}', found {}", c, found));
+        false
+    }
+
+    /// Parse items until `close` (or end of input for the top level).
+    fn items(&mut self, close: Option<char>) -> Vec<Item> {
+        let mut items = Vec::new();
+        loop {
+            match self.peek().clone() {
+                Tok::Eof => {
+                    if let Some(c) = close {
+                        let span = self.span();
+                        self.error(span, format!("Expected '{}' before end of file", c));
+                    }
+                    return items;
+                }
+                Tok::Sym(c) if Some(c) == close => return items,
+                Tok::Sym(',') => {
+                    self.bump();
+                }
+                Tok::Ident(name) => {
+                    if let Some(item) = self.item(name) {
+                        items.push(item);
+                    }
+                }
+                other => {
+                    let span = self.span();
+                    self.error(span, format!("Unexpected {}", describe(&other)));
+                    if matches!(other, Tok::Sym('}' | ')' | ']')) {
+                        self.bump();
+                    } else {
+                        self.recover();
+                    }
+                }
+            }
+        }
+    }
+
+    fn item(&mut self, name: String) -> Option<Item> {
+        let ident = self.bump();
+        if name == "for" && self.peek_at(1) == &Tok::Ident("in".into()) {
+            if let Tok::Ident(var) = self.peek().clone() {
+                return self.for_loop(var, ident.span);
+            }
+        }
+        if let ("let", Tok::Ident(var)) = (name.as_str(), self.peek().clone()) {
+            let name_span = self.bump().span;
+            if !self.expect_sym('=') {
+                self.recover();
+                return None;
+            }
+            let value = self.expr()?;
+            return Some(Item::Let(Let {
+                name: var,
+                name_span,
+                span: ident.span.start..value.span.end,
+                value,
+            }));
+        }
+        match self.peek().clone() {
+            Tok::Sym('=') => {
+                self.bump();
+                let value = self.expr()?;
+                Some(Item::Field(Field {
+                    key: name,
+                    key_span: ident.span,
+                    value,
+                }))
+            }
+            Tok::Sym('(') => {
+                let call = self.call(name, ident.span)?;
+                Some(Item::Call(call))
+            }
+            Tok::Sym('{') | Tok::Str(_) => {
+                let label = match self.peek().clone() {
+                    Tok::Str(s) => Some((s, self.bump().span)),
+                    _ => None,
+                };
+                if !self.expect_sym('{') {
+                    self.recover();
+                    return None;
+                }
+                let items = self.items(Some('}'));
+                self.eat_sym('}');
+                Some(Item::Block(Block {
+                    keyword: name,
+                    keyword_span: ident.span.clone(),
+                    label,
+                    items,
+                    span: ident.span.start..self.prev_end(),
+                }))
+            }
+            other => {
+                self.error(
+                    ident.span.clone(),
+                    format!(
+                        "Expected '=', '(' or '{{' after '{}', found {}",
+                        name,
+                        describe(&other)
+                    ),
+                );
+                self.recover();
+                None
+            }
+        }
+    }
+
+    /// `for var in iterable { items }` — `for` has already been consumed.
+    fn for_loop(&mut self, var: String, keyword_span: Span) -> Option<Item> {
+        let var_span = self.bump().span;
+        self.bump(); // `in`
+        let first = self.sum()?;
+        let iterable = if self.peek() == &Tok::Op("..") {
+            self.bump();
+            let end = self.sum()?;
+            Expr {
+                span: first.span.start..end.span.end,
+                kind: ExprKind::Range(Box::new(first), Box::new(end)),
+            }
+        } else {
+            first
+        };
+        if !self.expect_sym('{') {
+            self.recover();
+            return None;
+        }
+        let items = self.items(Some('}'));
+        self.eat_sym('}');
+        Some(Item::For(For {
+            var,
+            var_span,
+            iterable,
+            items,
+            span: keyword_span.start..self.prev_end(),
+            keyword_span,
+        }))
+    }
+
+    /// Skip ahead to something that can plausibly start the next item: a
+    /// separator, a closing brace, or the token after a balanced group.
+    fn recover(&mut self) {
+        loop {
+            match self.peek() {
+                Tok::Eof | Tok::Sym(',') | Tok::Sym('}') => return,
+                Tok::Sym(c @ ('{' | '(' | '[')) => {
+                    let close = match c {
+                        '{' => '}',
+                        '(' => ')',
+                        _ => ']',
+                    };
+                    self.bump();
+                    self.skip_balanced(close);
+                    return;
+                }
+                Tok::Ident(_) if self.pos > 0 && self.starts_item() => return,
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    /// Whether the current identifier looks like the start of a new item.
+    fn starts_item(&self) -> bool {
+        match self.peek_at(1) {
+            Tok::Sym('=') | Tok::Sym('{') | Tok::Sym('(') | Tok::Str(_) => true,
+            Tok::Ident(_) => matches!(self.peek(), Tok::Ident(k) if k == "let" || k == "for"),
+            _ => false,
+        }
+    }
+
+    fn skip_balanced(&mut self, close: char) {
+        let mut depth = 1usize;
+        loop {
+            match self.bump().tok {
+                Tok::Eof => return,
+                Tok::Sym('{' | '(' | '[') => depth += 1,
+                Tok::Sym(c) if c == close || "})]".contains(c) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// A value, optionally followed by `-> value` to form an interval.
+    fn expr(&mut self) -> Option<Expr> {
+        let first = self.sum()?;
+        if self.peek() != &Tok::Op("->") {
+            return Some(first);
+        }
+        self.bump();
+        let second = self.sum()?;
+        let span = first.span.start..second.span.end;
+        Some(Expr {
+            kind: ExprKind::Interval(Box::new(first), Box::new(second)),
+            span,
+        })
+    }
+
+    /// The arithmetic operator at the current token, if it is one of `ops`.
+    fn at_arith(&self, ops: &[char]) -> Option<char> {
+        let op = match self.peek() {
+            Tok::Sym('-') => '-',
+            Tok::Op(op) => op.chars().next().filter(|_| op.len() == 1)?,
+            _ => return None,
+        };
+        ops.contains(&op).then_some(op)
+    }
+
+    /// One left-associative level of arithmetic.
+    fn binary(&mut self, ops: &[char], operand: fn(&mut Self) -> Option<Expr>) -> Option<Expr> {
+        let mut lhs = operand(self)?;
+        while let Some(op) = self.at_arith(ops) {
+            self.bump();
+            let rhs = operand(self)?;
+            let span = lhs.span.start..rhs.span.end;
+            lhs = Expr {
+                kind: ExprKind::Binary {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                },
+                span,
+            };
+        }
+        Some(lhs)
+    }
+
+    fn sum(&mut self) -> Option<Expr> {
+        self.binary(&['+', '-'], Self::term)
+    }
+
+    fn term(&mut self) -> Option<Expr> {
+        self.binary(&['*', '/', '%'], Self::unary)
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        if !self.at_sym('-') {
+            return self.power();
+        }
+        let start = self.bump().span.start;
+        let inner = self.unary()?;
+        Some(Expr {
+            kind: ExprKind::Neg(Box::new(inner)),
+            span: start..self.prev_end(),
+        })
+    }
+
+    /// `^` is right-associative and binds tighter than unary minus.
+    fn power(&mut self) -> Option<Expr> {
+        let base = self.primary()?;
+        if self.at_arith(&['^']).is_none() {
+            return Some(base);
+        }
+        self.bump();
+        let exp = self.unary()?;
+        let span = base.span.start..exp.span.end;
+        Some(Expr {
+            kind: ExprKind::Binary {
+                op: '^',
+                lhs: Box::new(base),
+                rhs: Box::new(exp),
+            },
+            span,
+        })
+    }
+
+    fn primary(&mut self) -> Option<Expr> {
+        let start = self.span().start;
+        let tok = self.bump();
+        let kind = match tok.tok {
+            Tok::Num(n) => ExprKind::Number(n),
+            Tok::Str(s) => ExprKind::Str(s),
+            Tok::Ident(name) => {
+                if self.at_sym('(') {
+                    return self.call(name, tok.span);
+                }
+                ExprKind::Ident(name)
+            }
+            Tok::Sym('(') => {
+                let first = self.expr()?;
+                if self.eat_sym(')') {
+                    // `(expr)` is plain grouping, not a one-element tuple
+                    return Some(Expr {
+                        kind: first.kind,
+                        span: start..self.prev_end(),
+                    });
+                }
+                self.expect_sym(',');
+                let mut elems = vec![first];
+                elems.extend(self.expr_list(')')?);
+                ExprKind::Tuple(elems)
+            }
+            Tok::Sym('[') => ExprKind::List(self.expr_list(']')?),
+            other => {
+                self.error(
+                    tok.span.clone(),
+                    format!("Expected a value, found {}", describe(&other)),
+                );
+                if other == Tok::Eof {
+                    return None;
+                }
+                self.recover();
+                return None;
+            }
+        };
+        Some(Expr {
+            kind,
+            span: start..self.prev_end(),
+        })
+    }
+
+    /// Comma-separated expressions up to and including `close`.
+    fn expr_list(&mut self, close: char) -> Option<Vec<Expr>> {
+        let mut elems = Vec::new();
+        while !self.at_sym(close) {
+            elems.push(self.expr()?);
+            if !self.eat_sym(',') {
+                break;
+            }
+        }
+        if !self.expect_sym(close) {
+            self.recover();
+            return None;
+        }
+        Some(elems)
+    }
+
+    /// `name(args)` — the name has already been consumed.
+    fn call(&mut self, name: String, name_span: Span) -> Option<Expr> {
+        self.expect_sym('(');
+        let mut args = Vec::new();
+        while !self.at_sym(')') {
+            let arg_name = match (self.peek().clone(), self.peek_at(1)) {
+                (Tok::Ident(n), Tok::Sym('=')) => {
+                    self.bump();
+                    self.bump();
+                    Some(n)
+                }
+                _ => None,
+            };
+            let value = self.expr()?;
+            args.push(Arg {
+                name: arg_name,
+                value,
+            });
+            if !self.eat_sym(',') {
+                break;
+            }
+        }
+        if !self.expect_sym(')') {
+            self.recover();
+            return None;
+        }
+        Some(Expr {
+            kind: ExprKind::Call { name, args },
+            span: name_span.start..self.prev_end(),
+        })
+    }
+}
+
+fn describe(tok: &Tok) -> String {
+    match tok {
+        Tok::Ident(s) => format!("'{}'", s),
+        Tok::Num(n) => format!("number {}", n),
+        Tok::Str(s) => format!("string \"{}\"", s),
+        Tok::Sym(c) => format!("'{}'", c),
+        Tok::Op(op) => format!("'{}'", op),
+        Tok::Eof => "end of file".to_string(),
+    }
+}
+
+// ─── Header ───────────────────────────────────────────────────────────────────
+
+/// Match call arguments to the parameter list `params`, positional first.
+/// Unknown names, repeated parameters and surplus arguments are reported.
+fn bind_args<'e, const N: usize>(
+    src: &str,
+    call: &str,
+    args: &'e [Arg],
+    params: [&str; N],
+    diags: &mut Vec<Diagnostic>,
+) -> [Option<&'e Expr>; N] {
+    let mut bound: [Option<&Expr>; N] = [None; N];
+    let mut next_positional = 0usize;
+    for arg in args {
+        let slot = match &arg.name {
+            Some(n) => match params.iter().position(|p| p == n) {
+                Some(i) => i,
+                None => {
+                    diags.push(Diagnostic::at(
+                        src,
+                        arg.value.span.clone(),
+                        format!("Unknown argument '{}' for {}()", n, call),
+                    ));
+                    continue;
+                }
+            },
+            None => {
+                let i = next_positional;
+                next_positional += 1;
+                if i >= N {
+                    let message = match N {
+                        0 => format!("{}() takes no arguments", call),
+                        _ => format!("{}() takes at most {} arguments", call, N),
+                    };
+                    diags.push(Diagnostic::at(src, arg.value.span.clone(), message));
+                    continue;
+                }
+                i
+            }
+        };
+        if bound[slot].is_some() {
+            diags.push(Diagnostic::at(
+                src,
+                arg.value.span.clone(),
+                format!("Argument '{}' given more than once", params[slot]),
+            ));
+            continue;
+        }
+        bound[slot] = Some(&arg.value);
+    }
+    bound
+}
+
+/// Read a whole number in `min..=max` from an optional header argument.
+fn header_int(
+    src: &str,
+    expr: Option<&Expr>,
+    min: u32,
+    max: u32,
+    diags: &mut Vec<Diagnostic>,
+) -> Option<u32> {
+    let expr = expr?;
+    let span = expr.span.clone();
+    match expr_number(expr) {
+        Some(v) if v.fract() != 0.0 => {
+            diags.push(Diagnostic::at(src, span, "Expected a whole number"));
+            None
+        }
+        Some(v) if v < min as f32 || v > max as f32 => {
+            diags.push(Diagnostic::at(
+                src,
+                span,
+                format!("Value {} is out of range ({}..={})", v, min, max),
+            ));
+            None
+        }
+        Some(v) => Some(v as u32),
+        None => {
+            diags.push(Diagnostic::at(src, span, "Expected a number"));
+            None
+        }
+    }
+}
+
+// ─── Lowering ─────────────────────────────────────────────────────────────────
+
+/// Turn the syntax tree into statements.  Lowering is lenient: anything it
+/// does not understand is skipped, leaving the reporting to the validator.
+/// The one exception is a top-level `move` whose `element` does not resolve,
+/// which is reported here because the animation would otherwise vanish.
+fn lower_items(src: &str, items: &[Item], diags: &mut Vec<Diagnostic>) -> Vec<Statement> {
+    // problems with the bindings themselves are the validator's to report
+    let scope = Scope::collect(src, items, &mut Vec::new());
+    let mut lowered = Lowered::default();
+    let mut budget = MAX_ITERATIONS;
+    lower_level(src, items, &scope, &mut budget, &mut lowered);
+    let mut shapes = lowered.shapes;
+
+    // Top-level moves attach to their element after every shape is known,
+    // so a move may come before the element it animates.
+    for (block, anim, target) in lowered.moves {
+        match target {
+            Some((target, span)) => {
+                match shapes.iter_mut().find(|s: &&mut Shape| s.name() == target) {
+                    Some(shape) => shape.animations_mut().push(anim),
+                    None => diags.push(Diagnostic::at(
+                        src,
+                        span,
+                        format!("No element named \"{}\"", target),
+                    )),
+                }
+            }
+            None => diags.push(Diagnostic::at(
+                src,
+                block.keyword_span.clone(),
+                "Top-level 'move' needs element = \"<name>\"",
+            )),
+        }
+    }
+
+    shapes.into_iter().map(Statement::Shape).collect()
+}
+
+/// A top-level move with the element it names, if it names one.
+type TopLevelMove<'i> = (&'i Block, Animation, Option<(String, Span)>);
+
+/// Shapes and top-level moves in source order, with `for` loops expanded.
+#[derive(Default)]
+struct Lowered<'i> {
+    shapes: Vec<Shape>,
+    moves: Vec<TopLevelMove<'i>>,
+}
+
+fn lower_level<'i>(
+    src: &str,
+    items: &'i [Item],
+    scope: &Scope,
+    budget: &mut usize,
+    out: &mut Lowered<'i>,
+) {
+    for item in items {
+        match item {
+            Item::Block(block) if block.keyword == "move" => {
+                let Some(anim) = lower_move(src, block, scope) else {
+                    continue;
+                };
+                let target = match move_target(block) {
+                    Some((name, span)) => match lower_name(src, name, span, scope) {
+                        Ok(name) => Some((name, span.clone())),
+                        Err(_) => continue,
+                    },
+                    None => None,
+                };
+                out.moves.push((block, anim, target));
+            }
+            Item::Block(block) => {
+                if let Some(shape) = lower_shape(src, block, scope) {
+                    out.shapes.push(shape);
+                }
+            }
+            Item::For(lp) => {
+                for value in loop_values(src, lp, scope, budget).unwrap_or_default() {
+                    lower_level(src, &lp.items, &scope.iteration(lp, value), budget, out);
+                }
+            }
+            // `size(...)` / `timeline(...)` are read by `parse_config`
+            _ => {}
+        }
+    }
+}
+
+fn lower_shape(src: &str, block: &Block, scope: &Scope) -> Option<Shape> {
+    let name = match &block.label {
+        Some((name, span)) => lower_name(src, name, span, scope).ok()?,
+        None => String::new(),
+    };
+    match block.keyword.as_str() {
+        "rect" => {
+            let mut x = Property::Value(0.0);
+            let mut y = Property::Value(0.0);
+            let mut w = Property::Value(0.1);
+            let mut h = Property::Value(0.1);
+            let mut color = [255, 255, 255, 255];
+            let mut animations = Vec::new();
+            for item in &block.items {
+                match item {
+                    Item::Field(field) => match field.key.as_str() {
+                        "x" => set_property(src, &mut x, &field.value, scope),
+                        "y" => set_property(src, &mut y, &field.value, scope),
+                        "w" | "width" => set_property(src, &mut w, &field.value, scope),
+                        "h" | "height" => set_property(src, &mut h, &field.value, scope),
+                        "color" | "fill" => {
+                            if let Ok(c) = lower_color(src, &field.value, scope) {
+                                color = c;
+                            }
+                        }
+                        _ => {}
+                    },
+                    Item::Block(inner) if inner.keyword == "animations" => {
+                        animations.extend(lower_animations(src, inner, scope));
+                    }
+                    _ => {}
+                }
+            }
+            Some(Shape::Rect {
+                name,
+                x,
+                y,
+                w,
+                h,
+                color,
+                animations,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The contents of an `animations { ... }` block, in source order.
+fn lower_animations(src: &str, block: &Block, scope: &Scope) -> Vec<Animation> {
+    block
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Block(b) if b.keyword == "move" => lower_move(src, b, scope),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The `element = "..."` of a move block, with the span of its value.
+pub(crate) fn move_target(block: &Block) -> Option<(&str, &Span)> {
+    block.items.iter().find_map(|item| match item {
+        Item::Field(Field { key, value, .. }) if key == "element" => match &value.kind {
+            ExprKind::Str(s) => Some((s.as_str(), &value.span)),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// A `move { to, during, ease }` block.  `to` and `during` are required;
+/// `ease` defaults to linear.
+fn lower_move(src: &str, block: &Block, scope: &Scope) -> Option<Animation> {
+    let mut to = None;
+    let mut during = None;
+    let mut easing = Easing::Linear;
+    for item in &block.items {
+        let Item::Field(field) = item else { continue };
+        match field.key.as_str() {
+            "to" => to = lower_point(src, &field.value, scope).ok(),
+            "during" => during = lower_interval(src, &field.value, scope).ok(),
+            "ease" => {
+                if let Ok(e) = lower_easing(src, &field.value) {
+                    easing = e;
+                }
+            }
+            _ => {}
+        }
+    }
+    let (to_x, to_y) = to?;
+    let (start, end) = during?;
+    Some(Animation::Move {
+        to_x,
+        to_y,
+        start,
+        end,
+        easing,
+    })
+}
+
+// ─── Easing ───────────────────────────────────────────────────────────────────
+
+/// Read an `ease = ...` value.  Accepted forms:
+///
+/// - `linear`, `step`
+/// - `ease_in`, `ease_out`, `ease_in_out`, optionally `(power = 2.0)`
+/// - `cubic_bezier(x1, y1, x2, y2)`
+/// - `steps(n)` / `steps(n, jump_start | jump_end)`
+/// - `ease_{in,out,in_out}_back(overshoot = 1.70158)`
+/// - `ease_{in,out,in_out}_elastic(amplitude = 1.0, period = 0.3)`
+/// - `ease_{in,out,in_out}_bounce`
+/// - `back`, `elastic`, `bounce` as shorthands for the `ease_out_` forms
+/// - `spring(stiffness = 100, damping = 10, mass = 1)`
+///
+/// Arguments may be positional or named; omitted ones take the defaults
+/// shown (`cubic_bezier` needs all four).
+pub(crate) fn lower_easing(src: &str, expr: &Expr) -> Result<Easing, Vec<Diagnostic>> {
+    let (name, args) = match &expr.kind {
+        ExprKind::Ident(name) => (name.as_str(), &[][..]),
+        ExprKind::Call { name, args } => (name.as_str(), args.as_slice()),
+        _ => {
+            return Err(vec![Diagnostic::at(
+                src,
+                expr.span.clone(),
+                "Expected an easing such as linear or ease_in_out(power = 2)",
+            )])
+        }
+    };
+    let mut diags = Vec::new();
+    let easing = easing_call(src, expr, name, args, &mut diags);
+    match easing {
+        Some(easing) if diags.is_empty() => Ok(easing),
+        _ => Err(diags),
+    }
+}
+
+fn easing_call(
+    src: &str,
+    expr: &Expr,
+    name: &str,
+    args: &[Arg],
+    diags: &mut Vec<Diagnostic>,
+) -> Option<Easing> {
+    let positive = |v: f32| v > 0.0;
+    let (mode, family) = match name.strip_prefix("ease_in_out_") {
+        Some(family) => (EaseMode::InOut, family),
+        None => match name.strip_prefix("ease_in_") {
+            Some(family) => (EaseMode::In, family),
+            None => match name.strip_prefix("ease_out_") {
+                Some(family) => (EaseMode::Out, family),
+                None => (EaseMode::Out, name),
+            },
+        },
+    };
+
+    let easing = match (family, name) {
+        (_, "linear") => {
+            bind_args(src, name, args, [], diags);
+            Easing::Linear
+        }
+        (_, "step") => {
+            bind_args(src, name, args, [], diags);
+            Easing::Step
+        }
+        (_, "ease_in" | "ease_out" | "ease_in_out") => {
+            let [power] = bind_args(src, name, args, ["power"], diags);
+            let power = easing_number(src, power, "power", 2.0, positive, "positive", diags);
+            match name {
+                "ease_in" => Easing::EaseIn { power },
+                "ease_out" => Easing::EaseOut { power },
+                _ => Easing::EaseInOut { power },
+            }
+        }
+        (_, "cubic_bezier") => {
+            let bound = bind_args(src, name, args, ["x1", "y1", "x2", "y2"], diags);
+            if bound.iter().any(Option::is_none) {
+                diags.push(Diagnostic::at(
+                    src,
+                    expr.span.clone(),
+                    "cubic_bezier() needs x1, y1, x2 and y2",
+                ));
+                return None;
+            }
+            let unit = |v: f32| (0.0..=1.0).contains(&v);
+            let [x1, y1, x2, y2] = bound;
+            Easing::CubicBezier {
+                x1: easing_number(src, x1, "x1", 0.0, unit, "between 0 and 1", diags),
+                y1: easing_number(src, y1, "y1", 0.0, f32::is_finite, "finite", diags),
+                x2: easing_number(src, x2, "x2", 0.0, unit, "between 0 and 1", diags),
+                y2: easing_number(src, y2, "y2", 0.0, f32::is_finite, "finite", diags),
+            }
+        }
+        (_, "steps") => {
+            let [count, jump] = bind_args(src, name, args, ["count", "jump"], diags);
+            let Some(count_expr) = count else {
+                diags.push(Diagnostic::at(
+                    src,
+                    expr.span.clone(),
+                    "steps() needs a step count",
+                ));
+                return None;
+            };
+            let count = header_int(src, Some(count_expr), 1, 10_000, diags)?;
+            let jump = match jump.map(|j| (&j.kind, j)) {
+                None => StepJump::End,
+                Some((ExprKind::Ident(j), _)) if j == "jump_end" => StepJump::End,
+                Some((ExprKind::Ident(j), _)) if j == "jump_start" => StepJump::Start,
+                Some((_, j)) => {
+                    diags.push(Diagnostic::at(
+                        src,
+                        j.span.clone(),
+                        "Expected jump_start or jump_end",
+                    ));
+                    return None;
+                }
+            };
+            Easing::Steps { count, jump }
+        }
+        ("back", _) => {
+            let [overshoot] = bind_args(src, name, args, ["overshoot"], diags);
+            Easing::Back {
+                mode,
+                overshoot: easing_number(
+                    src,
+                    overshoot,
+                    "overshoot",
+                    1.70158,
+                    |v| v >= 0.0,
+                    "at least 0",
+                    diags,
+                ),
+            }
+        }
+        ("elastic", _) => {
+            let [amplitude, period] = bind_args(src, name, args, ["amplitude", "period"], diags);
+            Easing::Elastic {
+                mode,
+                amplitude: easing_number(
+                    src,
+                    amplitude,
+                    "amplitude",
+                    1.0,
+                    |v| v >= 1.0,
+                    "at least 1",
+                    diags,
+                ),
+                period: easing_number(src, period, "period", 0.3, positive, "positive", diags),
+            }
+        }
+        ("bounce", _) => {
+            bind_args(src, name, args, [], diags);
+            Easing::Bounce { mode }
+        }
+        (_, "spring") => {
+            let [stiffness, damping, mass] =
+                bind_args(src, name, args, ["stiffness", "damping", "mass"], diags);
+            Easing::Spring {
+                stiffness: easing_number(
+                    src,
+                    stiffness,
+                    "stiffness",
+                    100.0,
+                    positive,
+                    "positive",
+                    diags,
+                ),
+                damping: easing_number(
+                    src,
+                    damping,
+                    "damping",
+                    10.0,
+                    |v| v >= 0.0,
+                    "at least 0",
+                    diags,
+                ),
+                mass: easing_number(src, mass, "mass", 1.0, positive, "positive", diags),
+            }
+        }
+        _ => {
+            diags.push(Diagnostic::at(
+                src,
+                expr.span.clone(),
+                format!("Unknown easing '{}'", name),
+            ));
+            return None;
+        }
+    };
+    Some(easing)
+}
+
+/// Read an optional numeric easing argument, falling back to `default`.
+fn easing_number(
+    src: &str,
+    expr: Option<&Expr>,
+    param: &str,
+    default: f32,
+    valid: impl Fn(f32) -> bool,
+    requirement: &str,
+    diags: &mut Vec<Diagnostic>,
+) -> f32 {
+    let Some(expr) = expr else {
+        return default;
+    };
+    match expr_number(expr) {
+        Some(v) if valid(v) => v,
+        Some(_) => {
+            diags.push(Diagnostic::at(
+                src,
+                expr.span.clone(),
+                format!("'{}' must be {}", param, requirement),
+            ));
+            default
+        }
+        None => {
+            diags.push(Diagnostic::at(src, expr.span.clone(), "Expected a number"));
+            default
+        }
+    }
+}
+
+// ─── Value helpers ────────────────────────────────────────────────────────────
+
+fn set_property(src: &str, slot: &mut Property, expr: &Expr, scope: &Scope) {
+    if let Ok(p) = lower_property(src, expr, scope) {
+        *slot = p;
+    }
+}
+
+/// Variables a property expression can read, besides the evaluator's
+/// constants and the `let` bindings in scope.  They are set for every
+/// sampled time by [`crate::sampling`].
+pub const PROPERTY_VARIABLES: &[&str] = &["seconds", "frame"];
+
+/// Read a numeric shape property: a literal number, or an expression over
+/// [`PROPERTY_VARIABLES`], the bindings in `scope` and the evaluator's
+/// constants and functions, evaluated per frame.  The expression keeps its
+/// source with the bindings' values written in (see [`folded_source`]).
+pub(crate) fn lower_property(
+    src: &str,
+    expr: &Expr,
+    scope: &Scope,
+) -> Result<Property, Vec<Diagnostic>> {
+    if let Some(v) = expr_number(expr) {
+        return Ok(Property::Value(v));
+    }
+    if !is_numeric(expr) {
+        return Err(vec![Diagnostic::at(
+            src,
+            expr.span.clone(),
+            "Expected a number or an expression",
+        )]);
+    }
+    let compiled = compile_numeric(src, &expr.span, scope, PROPERTY_VARIABLES)?;
+    if compiled.variables().is_empty() {
+        // constant: catch e.g. a string binding used as a number now
+        compiled
+            .eval_number(&EvalContext::new())
+            .map_err(|e| vec![eval_error(src, e, expr.span.start)])?;
+    }
+    let folded = folded_source(src, &expr.span, scope).map_err(|d| vec![d])?;
+    Ok(Property::Expr(PropertyExpr::new(&folded).unwrap_or_else(
+        |_| PropertyExpr::from_compiled(&folded, compiled),
+    )))
+}
+
+/// The source at `span` with every name of a binding replaced by its
+/// value, e.g. `pad * 2` becomes `0.05 * 2`, so that it means the same
+/// without the `let`s and loops around it.  A binding whose value has no
+/// spelling in a numeric expression (a string, a list, or a number that
+/// overflowed) is an error, since its name is not in scope anywhere else.
+fn folded_source(src: &str, span: &Span, scope: &Scope) -> Result<String, Diagnostic> {
+    let text = &src[span.clone()];
+    let Ok(compiled) = evaluator::compile(text) else {
+        return Ok(text.to_string());
+    };
+    let mut out = String::new();
+    let mut pos = 0;
+    for (name, var) in compiled.variables() {
+        let Some(value) = scope
+            .lookup(name, span.start)
+            .and_then(|b| b.value.as_ref())
+        else {
+            continue;
+        };
+        let literal = match value {
+            Value::Number(n) if !n.is_finite() => Err("is not a finite number"),
+            Value::Number(n) if n.is_sign_negative() => Ok(format!("({})", n)),
+            Value::Number(n) => Ok(n.to_string()),
+            Value::Bool(b) => Ok(b.to_string()),
+            Value::Str(_) => Err("is a string, not a number"),
+            Value::List(_) => Err("is a list, not a number"),
+        };
+        let literal = literal.map_err(|why| {
+            let var = var.start + span.start..var.end + span.start;
+            Diagnostic::at(src, var, format!("'{}' {}", name, why))
+        })?;
+        out.push_str(&text[pos..var.start]);
+        out.push_str(&literal);
+        pos = var.end;
+    }
+    out.push_str(&text[pos..]);
+    Ok(out)
+}
+
+/// Compile the numeric expression at `span` (see [`is_numeric`]) with the
+/// values of the bindings it uses folded in.  `free` are the variables
+/// left for evaluation time; every other name must be a constant or a
+/// binding visible at `span`.
+fn compile_numeric(
+    src: &str,
+    span: &Span,
+    scope: &Scope,
+    free: &[&str],
+) -> Result<CompiledExpr, Vec<Diagnostic>> {
+    let text = &src[span.clone()];
+    let offset = span.start;
+    // `50%` means 0.5 in a literal, but `%` is the remainder in expressions
+    let units: Vec<Diagnostic> = lexer::tokenize(text)
+        .into_iter()
+        .filter(|t| matches!(t.kind, TokenKind::Number { unit: Some(_), .. }))
+        .map(|t| {
+            let span = t.span.start + offset..t.span.end + offset;
+            Diagnostic::at(src, span, "Units cannot be used inside an expression")
+        })
+        .collect();
+    if !units.is_empty() {
+        return Err(units);
+    }
+
+    let compiled = evaluator::compile(text).map_err(|e| vec![eval_error(src, e, offset)])?;
+    let mut values = EvalContext::new();
+    let mut diags = Vec::new();
+    let mut failed = false;
+    for (name, span) in compiled.variables() {
+        if free.contains(&name) || evaluator::CONSTANTS.iter().any(|(c, _)| *c == name) {
+            continue;
+        }
+        let span = span.start + offset..span.end + offset;
+        match scope.lookup(name, offset) {
+            Some(binding) => match &binding.value {
+                Some(v) => values.set_var(name, v.clone()),
+                // its `let` is already reported
+                None => failed = true,
+            },
+            None if PROPERTY_VARIABLES.contains(&name) => diags.push(Diagnostic::at(
+                src,
+                span,
+                format!("'{}' can only be used in shape properties", name),
+            )),
+            None => diags.push(Diagnostic::at(
+                src,
+                span,
+                format!("Undefined name '{}'", name),
+            )),
+        }
+    }
+    if failed || !diags.is_empty() {
+        return Err(diags);
+    }
+    Ok(compiled.bind(&values))
+}
+
+fn eval_error(src: &str, err: evaluator::EvalError, offset: usize) -> Diagnostic {
+    let err = err.offset(offset);
+    Diagnostic::at(src, err.span, err.message)
+}
+
+/// Compute a constant: a number, a string, a list of constants, or an
+/// expression over the bindings in `scope`.
+pub(crate) fn const_value(src: &str, expr: &Expr, scope: &Scope) -> Result<Value, Vec<Diagnostic>> {
+    if let Some(v) = expr_number(expr) {
+        return Ok(Value::Number(v));
+    }
+    match &expr.kind {
+        ExprKind::Str(s) => Ok(Value::Str(s.clone())),
+        ExprKind::List(items) => {
+            let mut values = Vec::new();
+            let mut diags = Vec::new();
+            for item in items {
+                match const_value(src, item, scope) {
+                    Ok(v) => values.push(v),
+                    Err(errors) => diags.extend(errors),
+                }
+            }
+            if diags.is_empty() {
+                Ok(Value::List(values))
+            } else {
+                Err(diags)
+            }
+        }
+        _ if is_numeric(expr) => compile_numeric(src, &expr.span, scope, &[])?
+            .eval(&EvalContext::new())
+            .map_err(|e| vec![eval_error(src, e, expr.span.start)]),
+        _ => Err(vec![Diagnostic::at(
+            src,
+            expr.span.clone(),
+            "Expected a number, a string, a list or an expression",
+        )]),
+    }
+}
+
+/// Compute a constant number; see [`const_value`].
+pub(crate) fn const_number(src: &str, expr: &Expr, scope: &Scope) -> Result<f32, Vec<Diagnostic>> {
+    match const_value(src, expr, scope)? {
+        Value::Number(n) => Ok(n),
+        other => Err(vec![Diagnostic::at(
+            src,
+            expr.span.clone(),
+            format!("Expected a number, found {}", other.type_name()),
+        )]),
+    }
+}
+
+/// Whether `expr` is something the evaluator can turn into a number.
+pub(crate) fn is_numeric(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Number(_) | ExprKind::Ident(_) => true,
+        ExprKind::Neg(inner) => is_numeric(inner),
+        ExprKind::Binary { lhs, rhs, .. } => is_numeric(lhs) && is_numeric(rhs),
+        ExprKind::Call { args, .. } => args
+            .iter()
+            .all(|arg| arg.name.is_none() && is_numeric(&arg.value)),
+        _ => false,
+    }
+}
+
+/// Read a literal number (optionally negated).
+pub(crate) fn expr_number(expr: &Expr) -> Option<f32> {
+    match &expr.kind {
+        ExprKind::Number(n) => Some(*n),
+        ExprKind::Neg(inner) => expr_number(inner).map(|v| -v),
+        _ => None,
+    }
+}
+
+/// An element name as written; inside a loop, every `{expr}` in it is
+/// replaced by the value of `expr`, e.g. `"bar_{i}"` becomes `"bar_3"`.
+/// `{{` and `}}` stand for a literal brace, in and outside loops.
+pub(crate) fn lower_name(
+    src: &str,
+    name: &str,
+    span: &Span,
+    scope: &Scope,
+) -> Result<String, Vec<Diagnostic>> {
+    if !name.contains(['{', '}']) {
+        return Ok(name.to_string());
+    }
+    // work on the source text, inside the quotes, so that every
+    // expression keeps its own span
+    let inner = span.start + 1..span.end.saturating_sub(1).max(span.start + 1);
+    let unescape = |from: usize, to: usize| lexer::unescape(&format!("\"{}\"", &src[from..to]));
+    let mut out = String::new();
+    let mut diags = Vec::new();
+    let mut pos = inner.start;
+    while let Some(open) = src[pos..inner.end].find(['{', '}']).map(|i| pos + i) {
+        out.push_str(&unescape(pos, open));
+        let brace = &src[open..open + 1];
+        let doubled = src[open + 1..inner.end].starts_with(brace);
+        // a lone `}` is kept as it is
+        if doubled || brace == "}" {
+            out.push_str(brace);
+            pos = open + brace.len() + usize::from(doubled);
+            continue;
+        }
+        let Some(close) = src[open..inner.end].find('}').map(|i| open + i) else {
+            diags.push(Diagnostic::at(src, open..open + 1, "Unclosed '{' in name"));
+            return Err(diags);
+        };
+        pos = close + 1;
+        if !scope.in_loop() {
+            diags.push(Diagnostic::at(
+                src,
+                open..pos,
+                "'{...}' in a name only works inside a 'for' loop; write '{{' for a '{'",
+            ));
+            continue;
+        }
+        let part = open + 1..close;
+        let value = compile_numeric(src, &part, scope, &[]).and_then(|c| {
+            c.eval(&EvalContext::new())
+                .map_err(|e| vec![eval_error(src, e, part.start)])
+        });
+        match value {
+            Ok(Value::Number(n)) => out.push_str(&n.to_string()),
+            Ok(Value::Str(s)) => out.push_str(&s),
+            Ok(Value::Bool(b)) => out.push_str(&b.to_string()),
+            Ok(Value::List(_)) => {
+                diags.push(Diagnostic::at(src, part, "A list cannot be part of a name"))
+            }
+            Err(errors) => diags.extend(errors),
+        }
+    }
+    out.push_str(&unescape(pos, inner.end));
+    if diags.is_empty() {
+        Ok(out)
+    } else {
+        Err(diags)
+    }
+}
+
+/// Most loop iterations a file may run, counting every iteration of
+/// nested loops, so a typo such as `0..1e9` cannot stall the editor.
+pub const MAX_ITERATIONS: usize = 10_000;
+
+/// The values the variable of `lp` takes, in order: the whole numbers of a
+/// `start..end` range (end exclusive), or the items of a list.  `budget`
+/// is the number of iterations the file may still run; a loop that would
+/// exceed it is an error and runs no iteration at all.
+pub(crate) fn loop_values(
+    src: &str,
+    lp: &For,
+    scope: &Scope,
+    budget: &mut usize,
+) -> Result<Vec<Value>, Vec<Diagnostic>> {
+    if super::scope::is_reserved(&lp.var) {
+        return Err(vec![Diagnostic::at(
+            src,
+            lp.var_span.clone(),
+            format!("'{}' is a built-in name and cannot be redefined", lp.var),
+        )]);
+    }
+    let too_many = || {
+        vec![Diagnostic::at(
+            src,
+            lp.keyword_span.clone(),
+            format!(
+                "Too many loop iterations; a file can run at most {} in total",
+                MAX_ITERATIONS
+            ),
+        )]
+    };
+
+    let values = match &lp.iterable.kind {
+        ExprKind::Range(a, b) => {
+            let (start, end) = match (const_number(src, a, scope), const_number(src, b, scope)) {
+                (Ok(start), Ok(end)) => (start, end),
+                (start, end) => {
+                    return Err(start.err().into_iter().chain(end.err()).flatten().collect())
+                }
+            };
+            for (v, e) in [(start, a), (end, b)] {
+                if !v.is_finite() || v.fract() != 0.0 {
+                    return Err(vec![Diagnostic::at(
+                        src,
+                        e.span.clone(),
+                        "Range bounds must be whole numbers",
+                    )]);
+                }
+            }
+            let count = (end - start).max(0.0);
+            if count > *budget as f32 {
+                return Err(too_many());
+            }
+            (0..count as usize)
+                .map(|k| Value::Number(start + k as f32))
+                .collect()
+        }
+        _ => match const_value(src, &lp.iterable, scope)? {
+            Value::List(items) => items,
+            other => {
+                return Err(vec![Diagnostic::at(
+                    src,
+                    lp.iterable.span.clone(),
+                    format!(
+                        "Expected a range 'start..end' or a list to loop over, found {}",
+                        other.type_name()
+                    ),
+                )])
+            }
+        },
+    };
+    if values.len() > *budget {
+        return Err(too_many());
+    }
+    *budget -= values.len();
+    Ok(values)
+}
+
+/// Read an `(x, y)` tuple of constant numbers.
+pub(crate) fn lower_point(
+    src: &str,
+    expr: &Expr,
+    scope: &Scope,
+) -> Result<(f32, f32), Vec<Diagnostic>> {
+    match &expr.kind {
+        ExprKind::Tuple(v) if v.len() == 2 => {
+            let x = const_number(src, &v[0], scope);
+            let y = const_number(src, &v[1], scope);
+            match (x, y) {
+                (Ok(x), Ok(y)) => Ok((x, y)),
+                (x, y) => Err(x.err().into_iter().chain(y.err()).flatten().collect()),
+            }
+        }
+        _ => Err(vec![Diagnostic::at(
+            src,
+            expr.span.clone(),
+            "Expected a point (x, y)",
+        )]),
+    }
+}
+
+/// Read a `start -> end` time range of constant numbers; `end` may not
+/// come before `start`.
+pub(crate) fn lower_interval(
+    src: &str,
+    expr: &Expr,
+    scope: &Scope,
+) -> Result<(f32, f32), Vec<Diagnostic>> {
+    let ExprKind::Interval(a, b) = &expr.kind else {
+        return Err(vec![Diagnostic::at(
+            src,
+            expr.span.clone(),
+            "Expected a time range 'start -> end'",
+        )]);
+    };
+    match (const_number(src, a, scope), const_number(src, b, scope)) {
+        (Ok(start), Ok(end)) if start <= end => Ok((start, end)),
+        (Ok(_), Ok(_)) => Err(vec![Diagnostic::at(
+            src,
+            expr.span.clone(),
+            "Time range needs numbers with start <= end",
+        )]),
+        (start, end) => Err(start.err().into_iter().chain(end.err()).flatten().collect()),
+    }
+}
+
+/// Read a color written as `"#rrggbb[aa]"`, as `[r, g, b(, a)]`, or as a
+/// binding holding either.
+pub(crate) fn lower_color(
+    src: &str,
+    expr: &Expr,
+    scope: &Scope,
+) -> Result<[u8; 4], Vec<Diagnostic>> {
+    let span = expr.span.clone();
+    let value = const_value(src, expr, scope)?;
+    let message = match &value {
+        Value::Str(s) => match super::utils::parse_hex_color(s) {
+            Some(c) => return Ok(c),
+            None => format!(
+                "Malformed hex color \"{}\", expected \"#rrggbb\" or \"#rrggbbaa\"",
+                s
+            ),
+        },
+        Value::List(items) if items.len() == 3 || items.len() == 4 => {
+            let mut c = [255u8; 4];
+            let channels = c.iter_mut().zip(items).all(|(slot, item)| match item {
+                Value::Number(v) if (0.0..=255.0).contains(v) => {
+                    *slot = v.round() as u8;
+                    true
+                }
+                _ => false,
+            });
+            if channels {
+                return Ok(c);
+            }
+            "Color lists need 3 or 4 numbers in 0..=255".to_string()
+        }
+        Value::List(_) => "Color lists need 3 or 4 numbers in 0..=255".to_string(),
+        other => format!("Expected a color, found {}", other.type_name()),
+    };
+    Err(vec![Diagnostic::at(src, span, message)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::generator::generate_dsl_from_elements;
+
+    fn sample_scene() -> Vec<Shape> {
+        vec![
+            Shape::Rect {
+                name: "Box".to_string(),
+                x: 0.1.into(),
+                y: 0.25.into(),
+                w: 0.2.into(),
+                h: 0.125.into(),
+                color: [0x78, 0xc8, 0xff, 255],
+                animations: vec![
+                    Animation::Move {
+                        to_x: 0.8,
+                        to_y: 0.25,
+                        start: 0.0,
+                        end: 2.0,
+                        easing: Easing::Linear,
+                    },
+                    Animation::Move {
+                        to_x: 0.1,
+                        to_y: 0.5,
+                        start: 2.5,
+                        end: 4.0,
+                        easing: Easing::EaseInOut { power: 3.0 },
+                    },
+                ],
+            },
+            Shape::Rect {
+                name: "Half \"Alpha\"".to_string(),
+                x: (-0.5).into(),
+                y: 1.5.into(),
+                w: 0.75.into(),
+                h: 0.001.into(),
+                color: [1, 2, 3, 0x80],
+                animations: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn generator_output_round_trips() {
+        let shapes = sample_scene();
+        let src = generate_dsl_from_elements(&shapes, 1280, 720, 60, 5.0);
+
+        let (stmts, diags) = parse_with_diagnostics(&src);
+        assert!(diags.is_empty(), "unexpected diagnostics: {:?}", diags);
+        let parsed: Vec<Shape> = stmts.into_iter().map(|Statement::Shape(s)| s).collect();
+        assert_eq!(parsed, shapes);
+
+        let regenerated = generate_dsl_from_elements(&parsed, 1280, 720, 60, 5.0);
+        assert_eq!(regenerated, src);
+    }
+
+    #[test]
+    fn generator_output_keeps_every_digit() {
+        let shapes = vec![Shape::Rect {
+            name: "Thirds".to_string(),
+            x: 0.1234.into(),
+            y: (1.0f32 / 3.0).into(),
+            w: 1e-5.into(),
+            h: (-2.0f32 / 7.0).into(),
+            color: [1, 2, 3, 4],
+            animations: vec![Animation::Move {
+                to_x: 0.1234,
+                to_y: 2.0 / 3.0,
+                start: 0.1,
+                end: 1.0 / 3.0,
+                easing: Easing::Linear,
+            }],
+        }];
+        let src = generate_dsl_from_elements(&shapes, 1280, 720, 60, 5.0);
+        let (stmts, diags) = parse_with_diagnostics(&src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let parsed: Vec<Shape> = stmts.into_iter().map(|Statement::Shape(s)| s).collect();
+        assert_eq!(parsed, shapes, "{}", src);
+    }
+
+    #[test]
+    fn generator_output_keeps_control_characters_in_names() {
+        let shapes = vec![Shape::Rect {
+            name: "a\nb\tc\rd\"e\\f{g}".to_string(),
+            x: 0.0.into(),
+            y: 0.0.into(),
+            w: 0.5.into(),
+            h: 0.5.into(),
+            color: [1, 2, 3, 255],
+            animations: vec![],
+        }];
+        let src = generate_dsl_from_elements(&shapes, 1280, 720, 60, 5.0);
+        let (stmts, diags) = parse_with_diagnostics(&src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let parsed: Vec<Shape> = stmts.into_iter().map(|Statement::Shape(s)| s).collect();
+        assert_eq!(parsed, shapes, "{}", src);
+    }
+
+    #[test]
+    fn accepts_aliases_comments_and_list_colors() {
+        let src = "// header\nrect \"R\" {\n  width = 0.5, height = 0.25 // trailing\n  fill = [10, 20, 30]\n}\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        assert_eq!(
+            stmts
+                .into_iter()
+                .map(|Statement::Shape(s)| s)
+                .collect::<Vec<_>>(),
+            vec![Shape::Rect {
+                name: "R".to_string(),
+                x: 0.0.into(),
+                y: 0.0.into(),
+                w: 0.5.into(),
+                h: 0.25.into(),
+                color: [10, 20, 30, 255],
+                animations: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn top_level_moves_attach_to_their_element() {
+        let src = "move {\n\telement = \"Box\",\n\tto = (0.7, 0.5),\n\tduring = 1 -> 2,\n\tease = ease_in(power = 3)\n}\n\
+                   rect \"Box\" {\n\tanimations {\n\t\tmove { to = (0.2, 0.2), during = 0 -> 1 }\n\t}\n}\n\
+                   move { element = \"Ghost\", to = (0, 0), during = 0 -> 1 }\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        let Statement::Shape(shape) = &stmts[0];
+        assert_eq!(
+            shape.animations(),
+            &[
+                Animation::Move {
+                    to_x: 0.2,
+                    to_y: 0.2,
+                    start: 0.0,
+                    end: 1.0,
+                    easing: Easing::Linear,
+                },
+                Animation::Move {
+                    to_x: 0.7,
+                    to_y: 0.5,
+                    start: 1.0,
+                    end: 2.0,
+                    easing: Easing::EaseIn { power: 3.0 },
+                },
+            ]
+        );
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].message, "No element named \"Ghost\"");
+        assert_eq!(&src[diags[0].span.clone()], "\"Ghost\"");
+    }
+
+    #[test]
+    fn expression_properties_keep_their_source() {
+        let src = "rect \"A\" {\n\tx = 0.5 + 0.2 * sin(seconds * tau),\n\tw = 0.1 + frame * 0.001\n\th = -2 ^ 2 + (1 - 0.5) / 2,\n}\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let Statement::Shape(shape) = &stmts[0];
+        let Shape::Rect { x, y, w, h, .. } = shape;
+        let source = |p: &Property| match p {
+            Property::Expr(e) => e.source().to_string(),
+            Property::Value(v) => panic!("literal {}", v),
+        };
+        assert_eq!(source(x), "0.5 + 0.2 * sin(seconds * tau)");
+        assert_eq!(source(w), "0.1 + frame * 0.001");
+        assert_eq!(source(h), "-2 ^ 2 + (1 - 0.5) / 2");
+        assert_eq!(y, &Property::Value(0.0));
+
+        let generated = generate_dsl_from_elements(std::slice::from_ref(shape), 1280, 720, 60, 5.0);
+        assert!(generated.contains("\tx = 0.5 + 0.2 * sin(seconds * tau),\n"));
+        let (reparsed, _) = parse_with_diagnostics(&generated);
+        let Statement::Shape(again) = &reparsed[0];
+        assert_eq!(again, shape);
+    }
+
+    #[test]
+    fn expression_property_errors() {
+        let errors = |value: &str| -> Vec<(String, String)> {
+            let src = format!("rect \"A\" {{ x = {} }}", value);
+            let (items, _) = parse_items(&src);
+            let Item::Block(block) = &items[0] else {
+                panic!("{:?}", items)
+            };
+            let Item::Field(field) = &block.items[0] else {
+                panic!("{:?}", block)
+            };
+            lower_property(&src, &field.value, &Scope::default())
+                .unwrap_err()
+                .into_iter()
+                .map(|d| (d.message, src[d.span].to_string()))
+                .collect()
+        };
+        assert_eq!(
+            errors("speed * seconds + drift"),
+            [
+                ("Undefined name 'speed'".into(), "speed".into()),
+                ("Undefined name 'drift'".into(), "drift".into()),
+            ]
+        );
+        assert_eq!(
+            errors("50% + seconds"),
+            [(
+                "Units cannot be used inside an expression".into(),
+                "50%".into()
+            )]
+        );
+        assert_eq!(
+            errors("wobble(seconds)"),
+            [("Unknown function 'wobble'".into(), "wobble".into())]
+        );
+        assert_eq!(
+            errors("\"left\""),
+            [(
+                "Expected a number or an expression".into(),
+                "\"left\"".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn expressions_read_names_and_operators_like_the_parser() {
+        let src = "let größe = 0.5\nrect \"R\" { x = größe * frame }\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let Statement::Shape(Shape::Rect { x, .. }) = &stmts[0];
+        assert_eq!(x.to_dsl(), "0.5 * frame");
+
+        let (_, diags) = parse_with_diagnostics("rect \"R\" { x = +frame }\n");
+        assert!(!diags.is_empty());
+    }
+
+    #[test]
+    fn let_bindings_fold_into_values() {
+        let src = "let accent = \"#ff3366\"\nlet pad = 0.05\nlet dur = pad * 20\n\
+                   rect \"A\" {\n\tx = pad,\n\ty = pad + frame,\n\tcolor = accent,\n\
+                   \tanimations { move { to = (1 - pad, pad), during = dur -> dur * 2 } }\n}\n\
+                   let pad = 0.25\nrect \"B\" { x = pad * 2 }\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let ctx = EvalContext::new()
+            .with_var("seconds", 0.05)
+            .with_var("frame", 3.0);
+        let Statement::Shape(Shape::Rect {
+            x,
+            y,
+            color,
+            animations,
+            ..
+        }) = &stmts[0];
+        assert_eq!(x.eval(&ctx), 0.05);
+        assert_eq!(y.eval(&ctx), 3.05);
+        assert_eq!(
+            (x.to_dsl(), y.to_dsl()),
+            ("0.05".into(), "0.05 + frame".into())
+        );
+        assert_eq!(color, &[0xff, 0x33, 0x66, 0xff]);
+        assert_eq!(
+            animations,
+            &[Animation::Move {
+                to_x: 0.95,
+                to_y: 0.05,
+                start: 1.0,
+                end: 2.0,
+                easing: Easing::Linear,
+            }]
+        );
+        // the second `pad` shadows the first from its `let` on
+        let Statement::Shape(b) = &stmts[1];
+        let Shape::Rect { x, .. } = b;
+        assert_eq!(x.eval(&ctx), 0.5);
+    }
+
+    #[test]
+    fn folded_expressions_never_name_bindings() {
+        let src = "let pad = 0.1\nrect \"A\" { x = pad * pi + seconds }\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let shapes: Vec<Shape> = stmts.into_iter().map(|Statement::Shape(s)| s).collect();
+        let Shape::Rect { x, .. } = &shapes[0];
+        assert_eq!(x.to_dsl(), "0.1 * pi + seconds");
+
+        // values with no spelling in an expression cannot be folded in
+        let src = "size(100, 100)\ntimeline(fps = 10, duration = 1)\n\
+                   let big = 300000000000000000000000000000000000000 * 10\n\
+                   let label = \"a\\\"b\"\nlet pair = [1, 2]\n\
+                   rect \"B\" { x = big * seconds, y = seconds + label, w = pair * frame }\n";
+        let diags = crate::dsl::validator::validate(src);
+        let messages: Vec<_> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "'big' is not a finite number",
+                "'label' is a string, not a number",
+                "'pair' is a list, not a number"
+            ]
+        );
+    }
+
+    #[test]
+    fn let_bindings_round_trip_through_to_dsl_and_serde() {
+        let src = "size(100, 100)\ntimeline(fps = 10, duration = 1)\n\
+                   let pad = 0.05\nlet neg = -pad\n\
+                   rect \"A\" { x = pad * 2, y = 1 + neg * sin(seconds) }\n\
+                   let pad = 0.25\n\
+                   for i in 0..2 { rect \"B_{i}\" { x = pad + i / 4, h = max(i, frame) } }\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let shapes: Vec<Shape> = stmts.into_iter().map(|Statement::Shape(s)| s).collect();
+        let Shape::Rect { x, y, .. } = &shapes[0];
+        assert_eq!(
+            (x.to_dsl(), y.to_dsl()),
+            ("0.05 * 2".into(), "1 + (-0.05) * sin(seconds)".into())
+        );
+        let Shape::Rect { x, h, .. } = &shapes[2];
+        assert_eq!(
+            (x.to_dsl(), h.to_dsl()),
+            ("0.25 + 1 / 4".into(), "max(1, frame)".into())
+        );
+
+        let generated = generate_dsl_from_elements(&shapes, 100, 100, 10, 1.0);
+        let (stmts, diags) = parse_with_diagnostics(&generated);
+        assert!(diags.is_empty(), "{:?}\n{}", diags, generated);
+        let reparsed: Vec<Shape> = stmts.into_iter().map(|Statement::Shape(s)| s).collect();
+        assert_eq!(reparsed, shapes);
+
+        let json = serde_json::to_string(&shapes).unwrap();
+        let restored: Vec<Shape> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, shapes);
+    }
+
+    #[test]
+    fn for_loops_expand_into_shapes() {
+        let src = "let colors = [\"#ff0000\", \"#00ff00\"]\n\
+                   for i in 0..3 {\n\trect \"bar_{i}\" { x = i * 0.25, h = 0.1 + i / 10 }\n}\n\
+                   for row in 0..2 { for col in 1..3 { rect \"cell_{row}_{col}\" { y = row } } }\n\
+                   for c in colors { rect \"swatch_{c}\" { color = c } }\n\
+                   for i in 1..3 { move { element = \"bar_{i}\", to = (1, i), during = i -> i + 1 } }\n\
+                   for i in 0..1 { rect \"{{lit}}_{i}\" {} }\n\
+                   rect \"top}}{{\" {}\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let shapes: Vec<Shape> = stmts.into_iter().map(|Statement::Shape(s)| s).collect();
+        let names: Vec<&str> = shapes.iter().map(Shape::name).collect();
+        assert_eq!(
+            names,
+            [
+                "bar_0",
+                "bar_1",
+                "bar_2",
+                "cell_0_1",
+                "cell_0_2",
+                "cell_1_1",
+                "cell_1_2",
+                "swatch_#ff0000",
+                "swatch_#00ff00",
+                "{lit}_0",
+                "top}{",
+            ]
+        );
+        // braces are escaped again when written back
+        assert!(shapes[10].to_dsl("").starts_with("rect \"top}}{{\" {"));
+
+        let ctx = EvalContext::new();
+        let Shape::Rect {
+            x, h, animations, ..
+        } = &shapes[2];
+        assert_eq!((x.eval(&ctx), h.eval(&ctx)), (0.5, 0.3));
+        assert_eq!(
+            animations,
+            &[Animation::Move {
+                to_x: 1.0,
+                to_y: 2.0,
+                start: 2.0,
+                end: 3.0,
+                easing: Easing::Linear,
+            }]
+        );
+        assert!(shapes[0].animations().is_empty());
+        let Shape::Rect { color, .. } = &shapes[8];
+        assert_eq!(color, &[0, 0xff, 0, 0xff]);
+    }
+
+    fn easing(src: &str) -> Result<Easing, Vec<String>> {
+        let full = format!("ease = {}", src);
+        let (items, diags) = parse_items(&full);
+        assert!(diags.is_empty(), "{:?}", diags);
+        let Item::Field(field) = &items[0] else {
+            panic!("expected a field")
+        };
+        lower_easing(&full, &field.value).map_err(|d| d.into_iter().map(|d| d.message).collect())
+    }
+
+    #[test]
+    fn every_easing_round_trips_through_to_dsl() {
+        let all = [
+            Easing::Linear,
+            Easing::Step,
+            Easing::EaseIn { power: 2.0 },
+            Easing::EaseOut { power: 3.5 },
+            Easing::EaseInOut { power: 1.0 },
+            Easing::CubicBezier {
+                x1: 0.68,
+                y1: -0.6,
+                x2: 0.32,
+                y2: 1.6,
+            },
+            Easing::Steps {
+                count: 5,
+                jump: StepJump::Start,
+            },
+            Easing::Steps {
+                count: 1,
+                jump: StepJump::End,
+            },
+            Easing::CubicBezier {
+                x1: 0.175,
+                y1: 0.885,
+                x2: 0.32,
+                y2: 1.275,
+            },
+            Easing::Back {
+                mode: EaseMode::InOut,
+                overshoot: 2.5,
+            },
+            Easing::Back {
+                mode: EaseMode::Out,
+                overshoot: 1.70158,
+            },
+            Easing::Elastic {
+                mode: EaseMode::In,
+                amplitude: 1.5,
+                period: 0.45,
+            },
+            Easing::Bounce {
+                mode: EaseMode::Out,
+            },
+            Easing::Spring {
+                stiffness: 170.0,
+                damping: 26.0,
+                mass: 1.0,
+            },
+        ];
+        for e in all {
+            assert_eq!(easing(&e.to_dsl()), Ok(e.clone()), "{}", e.to_dsl());
+        }
+        // the default overshoot is written in full, not rounded to 1.702
+        let back = Easing::Back {
+            mode: EaseMode::In,
+            overshoot: 1.70158,
+        };
+        assert_eq!(back.to_dsl(), "ease_in_back(overshoot = 1.70158)");
+    }
+
+    #[test]
+    fn easing_defaults_shorthands_and_errors() {
+        assert_eq!(easing("ease_in_out"), Ok(Easing::EaseInOut { power: 2.0 }));
+        assert_eq!(easing("ease_in(3)"), Ok(Easing::EaseIn { power: 3.0 }));
+        assert_eq!(
+            easing("steps(3)"),
+            Ok(Easing::Steps {
+                count: 3,
+                jump: StepJump::End
+            })
+        );
+        assert_eq!(
+            easing("elastic"),
+            Ok(Easing::Elastic {
+                mode: EaseMode::Out,
+                amplitude: 1.0,
+                period: 0.3
+            })
+        );
+        assert_eq!(
+            easing("spring(damping = 4)"),
+            Ok(Easing::Spring {
+                stiffness: 100.0,
+                damping: 4.0,
+                mass: 1.0
+            })
+        );
+
+        assert_eq!(
+            easing("wobble"),
+            Err(vec!["Unknown easing 'wobble'".into()])
+        );
+        assert_eq!(
+            easing("cubic_bezier(1.5, 0, 0.5, 1)"),
+            Err(vec!["'x1' must be between 0 and 1".into()])
+        );
+        assert_eq!(
+            easing("cubic_bezier(0.5, 0)"),
+            Err(vec!["cubic_bezier() needs x1, y1, x2 and y2".into()])
+        );
+        assert_eq!(
+            easing("steps(2, jump_both)"),
+            Err(vec!["Expected jump_start or jump_end".into()])
+        );
+        assert_eq!(
+            easing("ease_out(power = 0)"),
+            Err(vec!["'power' must be positive".into()])
+        );
+        assert_eq!(
+            easing("linear(1)"),
+            Err(vec!["linear() takes no arguments".into()])
+        );
+    }
+
+    #[test]
+    fn header_accepts_named_and_positional_arguments() {
+        let config = parse_config("size(width = 1920, height = 1080)\ntimeline(30, 2.5)\n")
+            .expect("valid header");
+        assert_eq!(
+            config,
+            HeaderConfig {
+                width: 1920,
+                height: 1080,
+                fps: 30,
+                duration: 2.5,
+            }
+        );
+    }
+
+    #[test]
+    fn header_fills_in_missing_fields() {
+        let config = parse_config("timeline(duration = 12)").expect("valid header");
+        assert_eq!(
+            config,
+            HeaderConfig {
+                duration: 12.0,
+                ..HeaderConfig::default()
+            }
+        );
+        assert_eq!(parse_config("rect \"A\" {}"), Ok(HeaderConfig::default()));
+    }
+
+    #[test]
+    fn header_range_errors_are_spanned() {
+        let src = "size(0, 720)\ntimeline(fps = 500, duration = 1, speed = 2)\n";
+        let diags = parse_config(src).unwrap_err();
+        let at: Vec<(usize, usize)> = diags.iter().map(|d| (d.line, d.column)).collect();
+        assert_eq!(at, vec![(1, 6), (2, 16), (2, 43)]);
+        assert_eq!(&src[diags[1].span.clone()], "500");
+    }
+
+    #[test]
+    fn syntax_error_is_reported_and_later_shapes_survive() {
+        let src = "rect \"A\" {\n\tx = = 1\n}\nrect \"B\" { x = 0.5 }\n";
+        let (stmts, diags) = parse_with_diagnostics(src);
+        assert_eq!(diags.len(), 1);
+        assert_eq!((diags[0].line, diags[0].column), (2, 6));
+        let names: Vec<String> = stmts
+            .iter()
+            .map(|Statement::Shape(s)| s.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["A", "B"]);
+    }
+}
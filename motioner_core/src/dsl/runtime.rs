@@ -23,6 +23,10 @@ pub fn run_handler(_shapes: &mut [Shape], _handler: &DslHandler, _ctx: &mut Eval
 }
 
 /// Stubbed block executor; never modifies the scene.
-pub fn exec_block(_shapes: &mut [Shape], _body: &str, _ctx: &mut EvalContext) -> Result<bool, String> {
+pub fn exec_block(
+    _shapes: &mut [Shape],
+    _body: &str,
+    _ctx: &mut EvalContext,
+) -> Result<bool, String> {
     Ok(false)
 }
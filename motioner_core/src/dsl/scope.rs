@@ -0,0 +1,144 @@
+//! Top-level `let` bindings.
+//!
+//! `let name = value` names a constant that later property and animation
+//! values can refer to:
+//!
+//! ```text
+//! let accent = "#ff3366"
+//! let pad = 0.05
+//! rect "Card" { x = pad, y = pad * 2, color = accent }
+//! ```
+//!
+//! The scoping rules are:
+//!
+//! - bindings are only allowed at the top level of a file, not inside
+//!   elements;
+//! - a binding is visible from the end of its `let` to the end of the file,
+//!   so a name used before its `let` is undefined;
+//! - a later `let` of the same name shadows the earlier one from there on,
+//!   while uses before it keep the earlier value;
+//! - `seconds`, `frame` and the evaluator's constants, functions and
+//!   keywords cannot be rebound;
+//! - values are computed once, when the file is parsed.  A value is a
+//!   number, a string, a list or an expression over earlier bindings; it
+//!   cannot read `seconds` or `frame`.
+//!
+//! Each iteration of a `for` loop gets a child scope in which the loop
+//! variable is bound inside the loop, shadowing any `let` of the same name.
+
+use super::ast::{For, Item, Span};
+use super::evaluator::{Value, CONSTANTS, FUNCTIONS, KEYWORDS};
+use super::parser::{const_value, PROPERTY_VARIABLES};
+use super::validator::Diagnostic;
+use std::cell::Cell;
+
+/// One `let` and its computed value.
+#[derive(Debug)]
+pub struct Binding {
+    pub name: String,
+    pub name_span: Span,
+    /// `None` when the value has an error; uses of the name are then not
+    /// reported a second time.
+    pub value: Option<Value>,
+    /// Where in the source the binding is visible.
+    visible: Span,
+    used: Cell<bool>,
+}
+
+/// The bindings of a file, in source order, or of one loop iteration.
+#[derive(Debug, Default)]
+pub struct Scope<'p> {
+    bindings: Vec<Binding>,
+    parent: Option<&'p Scope<'p>>,
+}
+
+impl<'p> Scope<'p> {
+    /// Compute the top-level `let`s of `items`, reporting reserved names
+    /// and invalid values.
+    pub fn collect(src: &str, items: &[Item], diags: &mut Vec<Diagnostic>) -> Self {
+        let mut scope = Self::default();
+        for item in items {
+            let Item::Let(binding) = item else { continue };
+            if is_reserved(&binding.name) {
+                diags.push(Diagnostic::at(
+                    src,
+                    binding.name_span.clone(),
+                    format!(
+                        "'{}' is a built-in name and cannot be redefined",
+                        binding.name
+                    ),
+                ));
+                continue;
+            }
+            let value = match const_value(src, &binding.value, &scope) {
+                Ok(v) => Some(v),
+                Err(errors) => {
+                    diags.extend(errors);
+                    None
+                }
+            };
+            scope.bindings.push(Binding {
+                name: binding.name.clone(),
+                name_span: binding.name_span.clone(),
+                value,
+                visible: binding.span.end..usize::MAX,
+                used: Cell::new(false),
+            });
+        }
+        scope
+    }
+
+    /// The scope of one iteration of `lp`, in which its variable is
+    /// `value`.
+    pub fn iteration(&'p self, lp: &For, value: Value) -> Scope<'p> {
+        Scope {
+            bindings: vec![Binding {
+                name: lp.var.clone(),
+                name_span: lp.var_span.clone(),
+                value: Some(value),
+                visible: lp.span.clone(),
+                used: Cell::new(false),
+            }],
+            parent: Some(self),
+        }
+    }
+
+    /// Whether this is the scope of a loop iteration.
+    pub fn in_loop(&self) -> bool {
+        self.parent.is_some()
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// The binding `name` refers to at `offset`, if any.  The binding is
+    /// marked as used.
+    pub fn lookup(&self, name: &str, offset: usize) -> Option<&Binding> {
+        let found = self
+            .bindings
+            .iter()
+            .rev()
+            .find(|b| b.name == name && b.visible.contains(&offset));
+        match found {
+            Some(b) => {
+                b.used.set(true);
+                Some(b)
+            }
+            None => self.parent?.lookup(name, offset),
+        }
+    }
+
+    /// Bindings of this scope that no lookup has found so far.
+    pub fn unused(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings.iter().filter(|b| !b.used.get())
+    }
+}
+
+/// Whether `name` has a built-in meaning in expressions.
+pub fn is_reserved(name: &str) -> bool {
+    PROPERTY_VARIABLES.contains(&name)
+        || KEYWORDS.contains(&name)
+        || CONSTANTS.iter().any(|(c, _)| *c == name)
+        || FUNCTIONS.iter().any(|(f, _)| *f == name)
+}
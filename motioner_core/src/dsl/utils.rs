@@ -0,0 +1,68 @@
+//! Minimal utility helpers for the DSL subsystem.
+//!
+//! Small text helpers shared by the parser, the generator and the editor.
+
+/// Validate DSL text and convert leading spaces to tabs.
+///
+/// This is a thin wrapper around the validator and the generator's
+/// `normalize_tabs` function.  Normalization runs first so the returned
+/// diagnostics point at the text the caller ends up holding.  They are later
+/// surfaced in the UI or used to block saves.
+pub fn validate_and_normalize(src: &mut String) -> Vec<super::validator::Diagnostic> {
+    let normalized = super::generator::normalize_tabs(src);
+    if normalized != *src {
+        *src = normalized;
+    }
+    super::validate(src)
+}
+
+/// Parse a hex color string like `"#RRGGBB"` or `"#RRGGBBAA"` into RGBA bytes.
+pub fn parse_hex_color(hex: &str) -> Option<[u8; 4]> {
+    let s = hex.strip_prefix('#')?;
+    if !s.is_ascii() || (s.len() != 6 && s.len() != 8) {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&s[i..i + 2], 16).ok();
+    let alpha = if s.len() == 8 { byte(6)? } else { 255 };
+    Some([byte(0)?, byte(2)?, byte(4)?, alpha])
+}
+
+/// Format RGBA bytes as `#rrggbb`, or `#rrggbbaa` when the color is not
+/// fully opaque.
+pub fn format_hex_color(color: [u8; 4]) -> String {
+    if color[3] == 255 {
+        format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+    } else {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color[0], color[1], color[2], color[3]
+        )
+    }
+}
+
+/// Quote an element name, doubling its braces so that they are not read
+/// as `{expr}` parts of the name.
+pub fn quote_name(name: &str) -> String {
+    quote(&name.replace('{', "{{").replace('}', "}}"))
+}
+
+/// Quote `s` as a DSL string literal, escaping `"`, `\` and the control
+/// characters that [`unescape`](super::lexer::unescape) decodes.
+pub fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
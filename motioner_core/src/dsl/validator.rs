@@ -0,0 +1,710 @@
+//! Semantic validation of DSL source.
+//!
+//! The parser only knows about the shape of the syntax; this module knows
+//! which element kinds and properties exist and what values they take, and
+//! reports every problem with the exact span it refers to.
+
+use super::ast::{Block, Expr, ExprKind, Field, For, Item, Span};
+use super::parser::{
+    is_numeric, loop_values, lower_color, lower_easing, lower_interval, lower_name, lower_point,
+    lower_property, move_target, MAX_ITERATIONS,
+};
+use super::scope::Scope;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// How serious a [`Diagnostic`] is.  Errors keep the code from being saved;
+/// warnings point at code that works but is probably a mistake.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+/// A problem found in DSL source, located both as a byte span (for the
+/// editor) and as a 1-based line/column pair (for humans and the gutter).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    /// Byte range of the offending source text.
+    pub span: Range<usize>,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+impl Diagnostic {
+    /// Build a diagnostic covering `span` of `src`.  Lines and columns are
+    /// 1-based and point at the start of the span; columns count
+    /// characters, not bytes.
+    pub fn at(src: &str, span: Range<usize>, message: impl Into<String>) -> Self {
+        let offset = span.start.min(src.len());
+        let before = &src[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+        Self {
+            message: message.into(),
+            line,
+            column,
+            span,
+            severity: Severity::Error,
+        }
+    }
+
+    /// Like [`Diagnostic::at`], for a warning.
+    pub fn warning(src: &str, span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::at(src, span, message)
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+// ─── Schema ───────────────────────────────────────────────────────────────────
+
+/// The kind of value a property accepts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ValueKind {
+    /// A literal number or an expression evaluated per frame
+    Number,
+    /// `"#rrggbb[aa]"` or `[r, g, b(, a)]`
+    Color,
+    Str,
+    /// `(x, y)`
+    Point,
+    /// `start -> end` in seconds
+    Interval,
+    /// `linear`, `ease_in_out(power = 2.0)`, ...
+    Easing,
+}
+
+/// A property and the aliases it may be written as.  The first name is the
+/// canonical one and is used for duplicate detection.
+struct PropSpec {
+    names: &'static [&'static str],
+    kind: ValueKind,
+}
+
+const RECT_PROPS: &[PropSpec] = &[
+    PropSpec {
+        names: &["x"],
+        kind: ValueKind::Number,
+    },
+    PropSpec {
+        names: &["y"],
+        kind: ValueKind::Number,
+    },
+    PropSpec {
+        names: &["w", "width"],
+        kind: ValueKind::Number,
+    },
+    PropSpec {
+        names: &["h", "height"],
+        kind: ValueKind::Number,
+    },
+    PropSpec {
+        names: &["color", "fill"],
+        kind: ValueKind::Color,
+    },
+];
+
+const MOVE_PROPS: &[PropSpec] = &[
+    PropSpec {
+        names: &["element"],
+        kind: ValueKind::Str,
+    },
+    PropSpec {
+        names: &["to"],
+        kind: ValueKind::Point,
+    },
+    PropSpec {
+        names: &["during"],
+        kind: ValueKind::Interval,
+    },
+    PropSpec {
+        names: &["ease"],
+        kind: ValueKind::Easing,
+    },
+];
+
+/// Schema for each shape keyword.
+fn shape_props(keyword: &str) -> Option<&'static [PropSpec]> {
+    match keyword {
+        "rect" => Some(RECT_PROPS),
+        _ => None,
+    }
+}
+
+// ─── Validator ────────────────────────────────────────────────────────────────
+
+/// Check DSL source for syntax and semantic errors.
+///
+/// Besides everything the parser reports, this flags unknown element kinds,
+/// unknown or duplicate properties, values of the wrong type, malformed hex
+/// colors, duplicate element names, `move` blocks whose `element` does
+/// not exist, undefined names and `for` loops that cannot run.  Loop
+/// bodies are checked once per iteration, with the names they generate.
+/// `let` bindings that are never used are reported as warnings.
+/// Diagnostics are returned in source order.  An empty file is valid.
+pub fn validate(src: &str) -> Vec<Diagnostic> {
+    let (items, mut diags) = super::parser::parse_items(src);
+    if items.is_empty() && diags.is_empty() {
+        return diags;
+    }
+    diags.extend(super::parser::read_header(src).1);
+
+    let scope = Scope::collect(src, &items, &mut diags);
+    let mut v = Validator {
+        src,
+        diags,
+        elements: HashMap::new(),
+        budget: MAX_ITERATIONS,
+    };
+    v.collect_elements(&items, &scope);
+    v.budget = MAX_ITERATIONS;
+    for item in &items {
+        v.top_level_item(item, &scope);
+    }
+    for binding in scope.unused() {
+        v.diags.push(Diagnostic::warning(
+            src,
+            binding.name_span.clone(),
+            format!("Unused name '{}'", binding.name),
+        ));
+    }
+
+    let mut diags = v.diags;
+    diags.sort_by_key(|d| d.span.start);
+    // every iteration of a loop reports its problems again, possibly with
+    // other names in them: keep one of each, and fold further errors at the
+    // same spot into the first
+    let mut seen: HashSet<(Range<usize>, String)> = HashSet::new();
+    let mut first_error: HashMap<Range<usize>, (usize, usize)> = HashMap::new();
+    let mut unique: Vec<Diagnostic> = Vec::new();
+    for d in diags {
+        if !seen.insert((d.span.clone(), d.message.clone())) {
+            continue;
+        }
+        if d.is_error() {
+            match first_error.entry(d.span.clone()) {
+                Entry::Occupied(mut first) => {
+                    first.get_mut().1 += 1;
+                    continue;
+                }
+                Entry::Vacant(slot) => {
+                    slot.insert((unique.len(), 0));
+                }
+            }
+        }
+        unique.push(d);
+    }
+    for (index, more) in first_error.into_values() {
+        if more > 0 {
+            unique[index].message += &format!(" (and {} more like it)", more);
+        }
+    }
+    unique
+}
+
+struct Validator<'a> {
+    src: &'a str,
+    diags: Vec<Diagnostic>,
+    /// Declared element names and where they were first declared.
+    elements: HashMap<String, Span>,
+    /// Loop iterations left for the current pass over the file.
+    budget: usize,
+}
+
+impl Validator<'_> {
+    fn error(&mut self, span: Span, message: impl Into<String>) {
+        self.diags.push(Diagnostic::at(self.src, span, message));
+    }
+
+    /// Run `check` with the scope of every iteration of `lp`.
+    fn iterations(&mut self, lp: &For, scope: &Scope, mut check: impl FnMut(&mut Self, &Scope)) {
+        match loop_values(self.src, lp, scope, &mut self.budget) {
+            Ok(values) => {
+                for value in values {
+                    check(self, &scope.iteration(lp, value));
+                }
+            }
+            Err(diags) => self.diags.extend(diags),
+        }
+    }
+
+    /// An element name with its interpolations done, or `None` after
+    /// reporting why they failed.
+    fn name(&mut self, name: &str, span: &Span, scope: &Scope) -> Option<String> {
+        match lower_name(self.src, name, span, scope) {
+            Ok(name) => Some(name),
+            Err(diags) => {
+                self.diags.extend(diags);
+                None
+            }
+        }
+    }
+
+    fn collect_elements(&mut self, items: &[Item], scope: &Scope) {
+        for item in items {
+            let block = match item {
+                Item::Block(block) => block,
+                Item::For(lp) => {
+                    self.iterations(lp, scope, |v, inner| v.collect_elements(&lp.items, inner));
+                    continue;
+                }
+                _ => continue,
+            };
+            if shape_props(&block.keyword).is_none() {
+                continue;
+            }
+            let Some((name, span)) = &block.label else {
+                self.error(
+                    block.keyword_span.clone(),
+                    format!(
+                        "'{}' needs a name, e.g. {} \"Box\" {{ ... }}",
+                        block.keyword, block.keyword
+                    ),
+                );
+                continue;
+            };
+            let Some(name) = self.name(name, span, scope) else {
+                continue;
+            };
+            match self.elements.entry(name) {
+                Entry::Vacant(slot) => {
+                    slot.insert(span.clone());
+                }
+                Entry::Occupied(slot) => {
+                    let message = format!("Duplicate element name \"{}\"", slot.key());
+                    self.error(span.clone(), message);
+                }
+            }
+        }
+    }
+
+    fn top_level_item(&mut self, item: &Item, scope: &Scope) {
+        match item {
+            Item::Block(block) => {
+                if let Some(props) = shape_props(&block.keyword) {
+                    self.shape(block, props, scope);
+                } else if block.keyword == "move" {
+                    self.move_block(block, None, scope);
+                } else {
+                    self.error(
+                        block.keyword_span.clone(),
+                        format!("Unknown element kind '{}'", block.keyword),
+                    );
+                }
+            }
+            Item::Call(call) => {
+                if let ExprKind::Call { name, .. } = &call.kind {
+                    if name != "size" && name != "timeline" {
+                        self.error(call.span.clone(), format!("Unknown directive '{}'", name));
+                    }
+                }
+            }
+            Item::Field(field) => {
+                self.error(
+                    field.key_span.clone(),
+                    format!("Property '{}' must be inside an element", field.key),
+                );
+            }
+            // checked when the scope is collected
+            Item::Let(_) => {}
+            Item::For(lp) => self.iterations(lp, scope, |v, inner| {
+                for item in &lp.items {
+                    match item {
+                        Item::Call(_) | Item::Let(_) => v.unexpected(item, "for"),
+                        other => v.top_level_item(other, inner),
+                    }
+                }
+            }),
+        }
+    }
+
+    fn shape(&mut self, block: &Block, props: &[PropSpec], scope: &Scope) {
+        let mut seen = HashMap::new();
+        let owner = block
+            .label
+            .as_ref()
+            .and_then(|(name, span)| lower_name(self.src, name, span, scope).ok());
+        for item in &block.items {
+            match item {
+                Item::Field(field) => self.field(field, props, &mut seen, &block.keyword, scope),
+                Item::Block(inner) if inner.keyword == "animations" => {
+                    for anim in &inner.items {
+                        match anim {
+                            Item::Block(b) if b.keyword == "move" => {
+                                self.move_block(b, owner.as_deref(), scope)
+                            }
+                            other => self.unexpected(other, "animations"),
+                        }
+                    }
+                }
+                other => self.unexpected(other, &block.keyword),
+            }
+        }
+    }
+
+    /// `owner` is the enclosing shape's name for moves nested in its
+    /// `animations` block, `None` for top-level moves.
+    fn move_block(&mut self, block: &Block, owner: Option<&str>, scope: &Scope) {
+        if let Some((_, span)) = &block.label {
+            self.error(span.clone(), "'move' does not take a name");
+        }
+        let mut seen = HashMap::new();
+        for item in &block.items {
+            match item {
+                Item::Field(field) => self.field(field, MOVE_PROPS, &mut seen, "move", scope),
+                other => self.unexpected(other, "move"),
+            }
+        }
+
+        for required in ["to", "during"] {
+            if !seen.contains_key(required) {
+                self.error(
+                    block.keyword_span.clone(),
+                    format!("'move' is missing '{}'", required),
+                );
+            }
+        }
+
+        let target = match move_target(block) {
+            Some((name, span)) => match self.name(name, span, scope) {
+                Some(name) => Some((name, span)),
+                None => return,
+            },
+            None => None,
+        };
+        match (target, owner) {
+            (Some((target, span)), None) => {
+                if !self.elements.contains_key(&target) {
+                    self.error(span.clone(), format!("No element named \"{}\"", target));
+                }
+            }
+            (Some((target, span)), Some(owner)) => {
+                if target != owner {
+                    self.error(
+                        span.clone(),
+                        format!(
+                            "A 'move' inside \"{}\" cannot animate \"{}\"; move it to the top level",
+                            owner, target
+                        ),
+                    );
+                }
+            }
+            (None, None) => self.error(
+                block.keyword_span.clone(),
+                "Top-level 'move' needs element = \"<name>\"",
+            ),
+            (None, Some(_)) => {}
+        }
+    }
+
+    fn field(
+        &mut self,
+        field: &Field,
+        props: &[PropSpec],
+        seen: &mut HashMap<&'static str, Span>,
+        owner: &str,
+        scope: &Scope,
+    ) {
+        let Some(spec) = props.iter().find(|p| p.names.contains(&field.key.as_str())) else {
+            self.error(
+                field.key_span.clone(),
+                format!("Unknown property '{}' for '{}'", field.key, owner),
+            );
+            return;
+        };
+        let canonical = spec.names[0];
+        if seen.insert(canonical, field.key_span.clone()).is_some() {
+            self.error(
+                field.key_span.clone(),
+                format!("Duplicate property '{}'", canonical),
+            );
+            return;
+        }
+        self.value(&field.value, spec.kind, &field.key, scope);
+    }
+
+    fn value(&mut self, expr: &Expr, kind: ValueKind, key: &str, scope: &Scope) {
+        let span = expr.span.clone();
+        let lowered = match kind {
+            ValueKind::Number => {
+                if !is_numeric(expr) {
+                    self.error(span, format!("Expected a number for '{}'", key));
+                    return;
+                }
+                lower_property(self.src, expr, scope).err()
+            }
+            ValueKind::Str => {
+                if !matches!(expr.kind, ExprKind::Str(_)) {
+                    self.error(span, format!("Expected a string for '{}'", key));
+                }
+                return;
+            }
+            ValueKind::Interval => {
+                if !matches!(expr.kind, ExprKind::Interval(..)) {
+                    self.error(
+                        span,
+                        format!("Expected a time range 'start -> end' for '{}'", key),
+                    );
+                    return;
+                }
+                lower_interval(self.src, expr, scope).err()
+            }
+            ValueKind::Easing => lower_easing(self.src, expr).err(),
+            ValueKind::Point => {
+                if !matches!(&expr.kind, ExprKind::Tuple(v) if v.len() == 2) {
+                    self.error(span, format!("Expected a point (x, y) for '{}'", key));
+                    return;
+                }
+                lower_point(self.src, expr, scope).err()
+            }
+            ValueKind::Color => {
+                if !matches!(expr.kind, ExprKind::Str(_) | ExprKind::List(_)) && !is_numeric(expr) {
+                    self.error(span, format!("Expected a color for '{}'", key));
+                    return;
+                }
+                lower_color(self.src, expr, scope).err()
+            }
+        };
+        self.diags.extend(lowered.into_iter().flatten());
+    }
+
+    fn unexpected(&mut self, item: &Item, owner: &str) {
+        let (span, what) = match item {
+            Item::Field(f) => (f.key_span.clone(), format!("property '{}'", f.key)),
+            Item::Block(b) => (b.keyword_span.clone(), format!("block '{}'", b.keyword)),
+            Item::Call(c) => (c.span.clone(), "call".to_string()),
+            Item::Let(l) => {
+                self.error(l.span.clone(), "'let' is only allowed at the top level");
+                return;
+            }
+            Item::For(f) => (f.keyword_span.clone(), "'for' loop".to_string()),
+        };
+        self.error(span, format!("Unexpected {} in '{}'", what, owner));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "size(1280, 720)\ntimeline(fps = 60, duration = 5.00)\n";
+
+    fn at(src: &str) -> Vec<(usize, usize, String)> {
+        validate(src)
+            .into_iter()
+            .map(|d| (d.line, d.column, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn generated_source_and_empty_file_are_clean() {
+        let shape = crate::scene::Shape::Rect {
+            name: "Box".to_string(),
+            x: 0.5.into(),
+            y: 0.5.into(),
+            w: 0.1.into(),
+            h: 0.1.into(),
+            color: [10, 20, 30, 255],
+            animations: vec![],
+        };
+        let src = crate::dsl::generator::generate_dsl_from_elements(&[shape], 1280, 720, 60, 5.0);
+        assert!(validate(&src).is_empty());
+        assert!(validate("").is_empty());
+        assert!(validate("  // nothing here\n").is_empty());
+    }
+
+    #[test]
+    fn reports_kinds_properties_types_and_colors() {
+        let src = format!(
+            "{}circle \"C\" {{}}\nrect \"R\" {{\n\tx = 0.1,\n\twidth = 0.2,\n\tw = 0.3,\n\ty = \"top\",\n\tcolour = \"#fff\",\n\tcolor = \"#12345g\"\n}}\n",
+            HEADER
+        );
+        let got = at(&src);
+        let lines: Vec<(usize, usize)> = got.iter().map(|(l, c, _)| (*l, *c)).collect();
+        assert_eq!(
+            lines,
+            vec![(3, 1), (7, 2), (8, 6), (9, 2), (10, 10)],
+            "{:?}",
+            got
+        );
+        assert!(got[0].2.contains("Unknown element kind 'circle'"));
+        assert!(got[1].2.contains("Duplicate property 'w'"));
+        assert!(got[2].2.contains("Expected a number for 'y'"));
+        assert!(got[3].2.contains("Unknown property 'colour'"));
+        assert!(got[4].2.contains("Malformed hex color"));
+    }
+
+    #[test]
+    fn reports_duplicate_names_and_dangling_moves() {
+        let src = format!(
+            "{}rect \"A\" {{}}\nrect \"A\" {{}}\nmove {{ element = \"B\", to = (0.5, 0.5), during = 0 -> 1 }}\nmove {{ to = (1, 1), during = 0 -> 1 }}\n",
+            HEADER
+        );
+        let got = at(&src);
+        let lines: Vec<(usize, usize)> = got.iter().map(|(l, c, _)| (*l, *c)).collect();
+        assert_eq!(lines, vec![(4, 6), (5, 18), (6, 1)], "{:?}", got);
+        assert!(got[0].2.contains("Duplicate element name"));
+        assert!(got[1].2.contains("No element named \"B\""));
+    }
+
+    #[test]
+    fn checks_nested_move_fields() {
+        let src = format!(
+            "{}rect \"A\" {{ animations {{\n\tmove {{ element = \"B\", to = (1, 1), during = 2 -> 1, ease = wobble }}\n\tmove {{ during = 0 -> 1 }}\n}} }}\nrect \"B\" {{}}\n",
+            HEADER
+        );
+        let got = at(&src);
+        let messages: Vec<&str> = got.iter().map(|(_, _, m)| m.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "A 'move' inside \"A\" cannot animate \"B\"; move it to the top level",
+                "Time range needs numbers with start <= end",
+                "Unknown easing 'wobble'",
+                "'move' is missing 'to'",
+            ],
+            "{:?}",
+            got
+        );
+    }
+
+    #[test]
+    fn checks_expression_properties() {
+        let src = format!(
+            "{}rect \"A\" {{\n\tx = 0.5 + 0.1 * sin(seconds * tau),\n\ty = frame * speed,\n\tw = 2s * frame\n}}\n",
+            HEADER
+        );
+        let got = at(&src);
+        assert_eq!(
+            got,
+            vec![
+                (5, 14, "Undefined name 'speed'".to_string()),
+                (
+                    6,
+                    6,
+                    "Units cannot be used inside an expression".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_let_bindings() {
+        let src = format!(
+            "{}let pad = gap * 2\nlet gap = 0.1\nlet spare = 3\nlet pi = 3\nlet t = seconds\nlet name = \"a\"\n\
+             rect \"A\" {{\n\tx = gap,\n\ty = name,\n\tlet w = 1\n\tcolor = gap\n}}\n",
+            HEADER
+        );
+        let got: Vec<_> = validate(&src)
+            .into_iter()
+            .map(|d| (d.line, d.column, d.is_error(), d.message))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (3, 5, false, "Unused name 'pad'".to_string()),
+                (3, 11, true, "Undefined name 'gap'".to_string()),
+                (5, 5, false, "Unused name 'spare'".to_string()),
+                (
+                    6,
+                    5,
+                    true,
+                    "'pi' is a built-in name and cannot be redefined".to_string()
+                ),
+                (7, 5, false, "Unused name 't'".to_string()),
+                (
+                    7,
+                    9,
+                    true,
+                    "'seconds' can only be used in shape properties".to_string()
+                ),
+                (11, 6, true, "Expected a number, found string".to_string()),
+                (
+                    12,
+                    2,
+                    true,
+                    "'let' is only allowed at the top level".to_string()
+                ),
+                (13, 10, true, "Expected a color, found number".to_string()),
+            ],
+            "{:?}",
+            got
+        );
+    }
+
+    #[test]
+    fn checks_for_loops() {
+        let src = format!(
+            "{}for i in 0..2 {{ rect \"same\" {{ x = i }} }}\n\
+             for i in 0..2.5 {{}}\n\
+             for i in 3 {{}}\n\
+             for i in 0..2 {{ rect \"b_{{j}}\" {{}}\n\tlet k = 1\n}}\n\
+             rect \"after\" {{ x = i\n\tfor n in 0..2 {{}}\n}}\n\
+             for i in 0..3 {{ move {{ element = \"nope_{{i}}\", to = (1, 1), during = 0 -> 1 }} }}\n\
+             rect \"top_{{i}}\" {{}}\n\
+             for i in 0..1 {{ rect \"lit{{{{x}}}}_{{i}}\" {{}} }}\n\
+             move {{ element = \"lit{{{{x}}}}_0\", to = (1, 1), during = 0 -> 1 }}\n\
+             for a in 0..100 {{ for b in 0..200 {{}} }}\n",
+            HEADER
+        );
+        let got: Vec<(String, &str)> = validate(&src)
+            .into_iter()
+            .map(|d| (d.message, &src[d.span]))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("Duplicate element name \"same\"".to_string(), "\"same\""),
+                ("Range bounds must be whole numbers".to_string(), "2.5"),
+                (
+                    "Expected a range 'start..end' or a list to loop over, found number"
+                        .to_string(),
+                    "3"
+                ),
+                ("Undefined name 'j'".to_string(), "j"),
+                (
+                    "'let' is only allowed at the top level".to_string(),
+                    "let k = 1"
+                ),
+                ("Undefined name 'i'".to_string(), "i"),
+                ("Unexpected 'for' loop in 'rect'".to_string(), "for"),
+                (
+                    "No element named \"nope_0\" (and 2 more like it)".to_string(),
+                    "\"nope_{i}\""
+                ),
+                (
+                    "'{...}' in a name only works inside a 'for' loop; write '{{' for a '{'"
+                        .to_string(),
+                    "{i}"
+                ),
+                (
+                    "Too many loop iterations; a file can run at most 10000 in total".to_string(),
+                    "for"
+                ),
+            ],
+            "{:?}",
+            got
+        );
+    }
+
+    #[test]
+    fn header_problems_surface_as_diagnostics() {
+        let got = validate("rect \"A\" {}\n");
+        assert_eq!(got.len(), 1);
+        assert!(got[0].message.contains("Missing project header"));
+        assert!(!got[0].is_error());
+    }
+}
@@ -0,0 +1,364 @@
+//! Animated GIF export.
+//!
+//! A GIF frame holds at most 256 colors and 1-bit transparency.  Every frame
+//! is quantized to its own palette with median cut, keeping one entry free
+//! for transparent pixels, optionally with Floyd–Steinberg dithering.  The
+//! result has few enough colors that `image`'s GIF encoder stores it as is.
+//!
+//! GIF delays are whole centiseconds, so frames do not all get the same
+//! delay: each frame ends at the centisecond nearest its exact end time,
+//! which keeps the loop as long as the timeline.
+
+use super::{render_in_order, ExportOutcome, ExportProgress, ExportSettings, LoopSettings};
+use crate::scene::Shape;
+use anyhow::Context;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// Opaque palette entries; the 256th is the transparent color.
+const MAX_COLORS: usize = 255;
+
+/// Browsers play shorter delays at 10 cs, so never go below this.
+const MIN_DELAY_CS: u32 = 2;
+
+/// Render `scene` into an animated GIF at `output`.
+pub fn export(
+    scene: &[Shape],
+    settings: &ExportSettings,
+    looping: &LoopSettings,
+    output: &Path,
+    progress: &ExportProgress,
+) -> anyhow::Result<ExportOutcome> {
+    if let Some(dir) = output.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("could not create {}", dir.display()))?;
+    }
+    let file = std::fs::File::create(output)
+        .with_context(|| format!("could not create {}", output.display()))?;
+    let mut writer = std::io::BufWriter::new(file);
+
+    let delays = frame_delays(settings);
+    let mut frames = 0;
+    let finished = {
+        let mut encoder = GifEncoder::new(&mut writer);
+        // GIF counts repeats after the first play, and a file without the
+        // NETSCAPE extension plays once
+        match looping.loop_count {
+            0 => encoder.set_repeat(Repeat::Infinite)?,
+            1 => {}
+            n => encoder.set_repeat(Repeat::Finite(n - 1))?,
+        }
+        render_in_order(scene, settings, progress, |i, mut frame| {
+            quantize(&mut frame, looping.dither);
+            let delay = Delay::from_numer_denom_ms(delays[i] * 10, 1);
+            encoder
+                .encode_frame(Frame::from_parts(frame, 0, 0, delay))
+                .with_context(|| format!("could not encode frame {}", i + 1))?;
+            frames = i + 1;
+            Ok(())
+        })
+    };
+
+    match finished {
+        Ok(true) => {
+            writer.flush()?;
+            Ok(ExportOutcome::Finished {
+                frames,
+                path: output.to_path_buf(),
+            })
+        }
+        Ok(false) => {
+            drop(writer);
+            let _ = std::fs::remove_file(output);
+            Ok(ExportOutcome::Cancelled { frames })
+        }
+        Err(err) => {
+            drop(writer);
+            let _ = std::fs::remove_file(output);
+            Err(err)
+        }
+    }
+}
+
+/// Per-frame delays in centiseconds whose running sum tracks the exact
+/// frame times at `settings.fps`.
+pub fn frame_delays(settings: &ExportSettings) -> Vec<u32> {
+    let fps = settings.fps.max(1) as f64;
+    let end_cs = |i: usize| (i as f64 * 100.0 / fps).round() as u32;
+    (0..settings.frame_count())
+        .map(|i| (end_cs(i + 1) - end_cs(i)).max(MIN_DELAY_CS))
+        .collect()
+}
+
+/// Reduce `frame` to at most [`MAX_COLORS`] opaque colors plus full
+/// transparency, in place.
+pub fn quantize(frame: &mut RgbaImage, dither: bool) {
+    let mut histogram: HashMap<[u8; 3], u32> = HashMap::new();
+    for px in frame.pixels_mut() {
+        if px[3] < 128 {
+            px.0 = [0, 0, 0, 0];
+        } else {
+            px[3] = 255;
+            *histogram.entry([px[0], px[1], px[2]]).or_default() += 1;
+        }
+    }
+    if histogram.len() <= MAX_COLORS {
+        return;
+    }
+
+    let mut palette = Palette::new(median_cut(&histogram, MAX_COLORS));
+    if !dither {
+        for px in frame.pixels_mut().filter(|px| px[3] != 0) {
+            let c = palette.nearest([px[0], px[1], px[2]]);
+            px.0 = [c[0], c[1], c[2], 255];
+        }
+        return;
+    }
+
+    // Floyd–Steinberg; transparent pixels neither take nor pass on error
+    let (w, h) = (frame.width() as usize, frame.height() as usize);
+    let mut error = vec![[0.0f32; 3]; w * h];
+    for y in 0..h {
+        for x in 0..w {
+            let px = frame.get_pixel_mut(x as u32, y as u32);
+            if px[3] == 0 {
+                continue;
+            }
+            let e = error[y * w + x];
+            let wanted: [f32; 3] = std::array::from_fn(|c| (px[c] as f32 + e[c]).clamp(0.0, 255.0));
+            let c = palette.nearest(wanted.map(|v| v.round() as u8));
+            px.0 = [c[0], c[1], c[2], 255];
+
+            let diff: [f32; 3] = std::array::from_fn(|i| wanted[i] - c[i] as f32);
+            let mut spread = |dx: isize, dy: usize, weight: f32| {
+                let nx = x as isize + dx;
+                if nx < 0 || nx as usize >= w || y + dy >= h {
+                    return;
+                }
+                let cell = &mut error[(y + dy) * w + nx as usize];
+                for i in 0..3 {
+                    cell[i] += diff[i] * weight;
+                }
+            };
+            spread(1, 0, 7.0 / 16.0);
+            spread(-1, 1, 3.0 / 16.0);
+            spread(0, 1, 5.0 / 16.0);
+            spread(1, 1, 1.0 / 16.0);
+        }
+    }
+}
+
+/// Median cut: keep splitting the box with the widest channel at its
+/// pixel-weighted median until there are `max` boxes, then average each.
+fn median_cut(histogram: &HashMap<[u8; 3], u32>, max: usize) -> Vec<[u8; 3]> {
+    let mut boxes: Vec<Vec<([u8; 3], u32)>> =
+        vec![histogram.iter().map(|(c, n)| (*c, *n)).collect()];
+    while boxes.len() < max {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let (channel, range) = widest_channel(b);
+                (i, channel, range)
+            })
+            .max_by_key(|&(_, _, range)| range);
+        let Some((index, channel, _)) = widest else {
+            break;
+        };
+
+        let mut lower = boxes.swap_remove(index);
+        lower.sort_unstable_by_key(|(c, _)| (c[channel], *c));
+        let total: u64 = lower.iter().map(|(_, n)| *n as u64).sum();
+        let mut seen = 0;
+        let median = lower
+            .iter()
+            .position(|(_, n)| {
+                seen += *n as u64;
+                seen * 2 >= total
+            })
+            .unwrap_or(0);
+        let upper = lower.split_off((median + 1).clamp(1, lower.len() - 1));
+        boxes.push(lower);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|b| {
+            let total: u64 = b.iter().map(|(_, n)| *n as u64).sum();
+            std::array::from_fn(|i| {
+                let sum: u64 = b.iter().map(|(c, n)| c[i] as u64 * *n as u64).sum();
+                ((sum + total / 2) / total) as u8
+            })
+        })
+        .collect()
+}
+
+fn widest_channel(colors: &[([u8; 3], u32)]) -> (usize, u8) {
+    (0..3)
+        .map(|i| {
+            let (lo, hi) = colors.iter().fold((u8::MAX, 0), |(lo, hi), (c, _)| {
+                (lo.min(c[i]), hi.max(c[i]))
+            });
+            (i, hi - lo)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap_or((0, 0))
+}
+
+/// A palette with a nearest-color cache; frames reuse few exact colors.
+struct Palette {
+    colors: Vec<[u8; 3]>,
+    cache: HashMap<[u8; 3], usize>,
+}
+
+impl Palette {
+    fn new(colors: Vec<[u8; 3]>) -> Self {
+        Self {
+            colors,
+            cache: HashMap::new(),
+        }
+    }
+
+    fn nearest(&mut self, color: [u8; 3]) -> [u8; 3] {
+        let colors = &self.colors;
+        let index = *self.cache.entry(color).or_insert_with(|| {
+            let distance = |p: &[u8; 3]| -> i32 {
+                (0..3).map(|i| (p[i] as i32 - color[i] as i32).pow(2)).sum()
+            };
+            (0..colors.len())
+                .min_by_key(|&i| distance(&colors[i]))
+                .unwrap_or(0)
+        });
+        colors[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::AnimationDecoder;
+
+    fn settings(fps: u32, duration: f32) -> ExportSettings {
+        ExportSettings {
+            width: 8,
+            height: 4,
+            fps,
+            duration,
+        }
+    }
+
+    #[test]
+    fn delays_add_up_to_the_timeline() {
+        let delays = frame_delays(&settings(30, 3.0));
+        assert_eq!(delays.len(), 90);
+        assert_eq!(delays.iter().sum::<u32>(), 300);
+        assert!(delays.iter().all(|d| (3..=4).contains(d)));
+        assert!(frame_delays(&settings(25, 1.0)).iter().all(|&d| d == 4));
+        // faster than GIF can play: clamped to the shortest honoured delay
+        assert!(frame_delays(&settings(120, 1.0)).iter().all(|&d| d == 2));
+    }
+
+    #[test]
+    fn quantizes_to_a_gif_palette() {
+        let gradient = RgbaImage::from_fn(64, 64, |x, y| {
+            let alpha = if x == 0 { 10 } else { 255 };
+            image::Rgba([x as u8 * 4, y as u8 * 4, 128, alpha])
+        });
+        for dither in [false, true] {
+            let mut img = gradient.clone();
+            quantize(&mut img, dither);
+            let colors: std::collections::HashSet<_> = img.pixels().map(|p| p.0).collect();
+            assert!(colors.len() <= 256, "{} colors", colors.len());
+            assert_eq!(img.get_pixel(0, 5).0, [0, 0, 0, 0]);
+            let px = img.get_pixel(40, 20).0;
+            assert_eq!(px[3], 255);
+            assert!((px[0] as i32 - 160).abs() < 24 && (px[1] as i32 - 80).abs() < 24);
+        }
+
+        // few colors are kept exactly
+        let mut flat = RgbaImage::from_pixel(4, 4, image::Rgba([1, 2, 3, 200]));
+        quantize(&mut flat, true);
+        assert!(flat.pixels().all(|p| p.0 == [1, 2, 3, 255]));
+    }
+
+    #[test]
+    fn writes_an_animated_gif() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("loop.gif");
+        let scene = crate::dsl::parse_dsl(
+            "rect \"A\" { x = 0.5, y = 0.5, w = 1, h = 1, color = \"#ff0000\" }",
+        );
+        let looping = LoopSettings {
+            loop_count: 0,
+            dither: true,
+        };
+        let outcome = export(
+            &scene,
+            &settings(30, 0.1),
+            &looping,
+            &output,
+            &ExportProgress::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            outcome,
+            ExportOutcome::Finished {
+                frames: 3,
+                path: output.clone()
+            }
+        );
+
+        let file = std::io::BufReader::new(std::fs::File::open(&output).unwrap());
+        let frames = image::codecs::gif::GifDecoder::new(file)
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 3);
+        let delays: Vec<_> = frames
+            .iter()
+            .map(|f| f.delay().numer_denom_ms().0)
+            .collect();
+        assert_eq!(delays, vec![30, 40, 30]);
+        assert_eq!(frames[1].buffer().get_pixel(3, 2).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn loop_count_is_the_number_of_plays() {
+        let dir = tempfile::tempdir().unwrap();
+        let scene = crate::dsl::parse_dsl("circle \"A\" { x = 0.5, y = 0.5, radius = 0.2 }");
+        for (loop_count, repeat) in [
+            (0, gif::Repeat::Infinite),
+            (1, gif::Repeat::Finite(0)),
+            (3, gif::Repeat::Finite(2)),
+        ] {
+            let output = dir.path().join(format!("{}.gif", loop_count));
+            let looping = LoopSettings {
+                loop_count,
+                dither: false,
+            };
+            export(
+                &scene,
+                &settings(10, 0.2),
+                &looping,
+                &output,
+                &ExportProgress::default(),
+            )
+            .unwrap();
+
+            let bytes = std::fs::read(&output).unwrap();
+            let has_extension = bytes.windows(11).any(|w| w == b"NETSCAPE2.0");
+            assert_eq!(has_extension, loop_count != 1, "loop_count {}", loop_count);
+            let mut decoder = gif::DecodeOptions::new()
+                .read_info(bytes.as_slice())
+                .unwrap();
+            while decoder.read_next_frame().unwrap().is_some() {}
+            assert_eq!(decoder.repeat(), repeat, "loop_count {}", loop_count);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Offline exporters.
+//!
+//! Every exporter renders through [`render_in_order`], which renders frames
+//! in parallel with rayon but hands them to the writer strictly in frame
+//! order.  Progress and cancellation go through a shared [`ExportProgress`]
+//! so the UI can watch a job running on another thread.
+
+pub mod apng;
+pub mod gif;
+pub mod png_sequence;
+pub mod video;
+
+use crate::scene::Shape;
+use crate::timing::Timeline;
+use image::RgbaImage;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use video::{VideoCodec, VideoSettings};
+
+/// The formats the export dialog offers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    #[default]
+    PngSequence,
+    /// A palette GIF; no ffmpeg needed.
+    Gif,
+    /// An animated PNG with full alpha.
+    Apng,
+    /// A single video file encoded by ffmpeg.
+    Video(VideoCodec),
+}
+
+impl ExportFormat {
+    pub const ALL: &'static [ExportFormat] = &[
+        ExportFormat::PngSequence,
+        ExportFormat::Gif,
+        ExportFormat::Apng,
+        ExportFormat::Video(VideoCodec::H264),
+        ExportFormat::Video(VideoCodec::Vp9),
+        ExportFormat::Video(VideoCodec::ProRes),
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::PngSequence => "PNG sequence",
+            ExportFormat::Gif => "GIF",
+            ExportFormat::Apng => "Animated PNG",
+            ExportFormat::Video(codec) => codec.label(),
+        }
+    }
+
+    /// Where an export of `project` in this format is written.
+    pub fn output_path(self, project: &Path) -> PathBuf {
+        let dir = project.join("export");
+        let extension = match self {
+            ExportFormat::PngSequence => return dir,
+            ExportFormat::Gif => "gif",
+            ExportFormat::Apng => "png",
+            ExportFormat::Video(codec) => codec.extension(),
+        };
+        let stem = project
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "motioner".to_string());
+        dir.join(format!("{}.{}", stem, extension))
+    }
+
+    /// Whether [`LoopSettings`] apply to this format.
+    pub fn loops(self) -> bool {
+        matches!(self, ExportFormat::Gif | ExportFormat::Apng)
+    }
+}
+
+/// Output size and timing of an export.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExportSettings {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    pub duration: f32,
+}
+
+impl ExportSettings {
+    pub fn timeline(&self) -> Timeline {
+        Timeline {
+            fps: self.fps,
+            duration: self.duration,
+        }
+    }
+
+    /// Number of frames: one per `1 / fps` seconds of the duration.
+    pub fn frame_count(&self) -> usize {
+        self.timeline().frame_count()
+    }
+
+    /// Scene time of frame `index` (0-based).
+    pub fn frame_time(&self, index: usize) -> f32 {
+        self.timeline().frame_time(index)
+    }
+}
+
+/// Options for the looping formats, GIF and APNG.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoopSettings {
+    /// How many times the animation plays; `0` loops forever.
+    pub loop_count: u16,
+    /// Dither GIF frames that need more colors than the palette holds.
+    pub dither: bool,
+}
+
+impl Default for LoopSettings {
+    fn default() -> Self {
+        Self {
+            loop_count: 0,
+            dither: true,
+        }
+    }
+}
+
+/// Shared between a running export and whoever watches it.
+#[derive(Debug, Default)]
+pub struct ExportProgress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl ExportProgress {
+    /// Frames written so far and the total to write.
+    pub fn frames(&self) -> (usize, usize) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Completed fraction in `0..=1`.
+    pub fn fraction(&self) -> f32 {
+        let (done, total) = self.frames();
+        if total == 0 {
+            0.0
+        } else {
+            done as f32 / total as f32
+        }
+    }
+
+    /// Ask the export to stop; no frame is written after this is seen.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// How an export ended when it did not fail.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExportOutcome {
+    /// Everything was written to `path` (a file, or a folder for sequences).
+    Finished {
+        frames: usize,
+        path: PathBuf,
+    },
+    Cancelled {
+        frames: usize,
+    },
+}
+
+/// Render every frame of `scene` and pass each to `write` in frame order.
+///
+/// Frames are rendered in parallel batches of a few per worker thread, so
+/// memory stays bounded however long the export is.  Cancellation is
+/// checked before every batch and every write, so no frame is written
+/// after it.
+/// Returns `Ok(false)` when cancelled; an error from `write` stops the
+/// export and is returned.
+pub fn render_in_order(
+    scene: &[Shape],
+    settings: &ExportSettings,
+    progress: &ExportProgress,
+    mut write: impl FnMut(usize, RgbaImage) -> anyhow::Result<()>,
+) -> anyhow::Result<bool> {
+    let total = settings.frame_count();
+    progress.total.store(total, Ordering::Relaxed);
+    progress.done.store(0, Ordering::Relaxed);
+
+    let batch = rayon::current_num_threads().max(1) * 2;
+    for start in (0..total).step_by(batch) {
+        if progress.is_cancelled() {
+            return Ok(false);
+        }
+        let end = (start + batch).min(total);
+        let frames: Vec<RgbaImage> = (start..end)
+            .into_par_iter()
+            .map(|i| {
+                crate::renderer::render_frame(
+                    scene,
+                    settings.frame_time(i),
+                    settings.fps,
+                    settings.width,
+                    settings.height,
+                )
+            })
+            .collect();
+        for (i, frame) in (start..end).zip(frames) {
+            if progress.is_cancelled() {
+                return Ok(false);
+            }
+            write(i, frame)?;
+            progress.done.store(i + 1, Ordering::Relaxed);
+        }
+    }
+    Ok(true)
+}
+
+/// Export `scene` in `format` to `output` on the calling thread.  `output`
+/// is a folder for image sequences and a file otherwise.
+pub fn run(
+    format: ExportFormat,
+    scene: &[Shape],
+    settings: &ExportSettings,
+    video: &VideoSettings,
+    looping: &LoopSettings,
+    output: &Path,
+    progress: &ExportProgress,
+) -> anyhow::Result<ExportOutcome> {
+    match format {
+        ExportFormat::PngSequence => png_sequence::export(scene, settings, output, progress),
+        ExportFormat::Gif => gif::export(scene, settings, looping, output, progress),
+        ExportFormat::Apng => apng::export(scene, settings, looping, output, progress),
+        ExportFormat::Video(codec) => {
+            video::export(scene, settings, codec, video, output, progress)
+        }
+    }
+}
+
+/// An export running on a background thread.
+pub struct ExportJob {
+    pub format: ExportFormat,
+    pub progress: Arc<ExportProgress>,
+    handle: std::thread::JoinHandle<anyhow::Result<ExportOutcome>>,
+}
+
+impl ExportJob {
+    /// Start exporting `scene` in `format` to
+    /// [`ExportFormat::output_path`] of `project`.
+    pub fn start(
+        format: ExportFormat,
+        scene: Vec<Shape>,
+        settings: ExportSettings,
+        video: VideoSettings,
+        looping: LoopSettings,
+        project: PathBuf,
+    ) -> Self {
+        let progress = Arc::new(ExportProgress::default());
+        let job_progress = Arc::clone(&progress);
+        let handle = std::thread::spawn(move || {
+            let output = format.output_path(&project);
+            run(
+                format,
+                &scene,
+                &settings,
+                &video,
+                &looping,
+                &output,
+                &job_progress,
+            )
+        });
+        Self {
+            format,
+            progress,
+            handle,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Wait for the job and return its result.
+    pub fn join(self) -> anyhow::Result<ExportOutcome> {
+        self.handle
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("export thread panicked")))
+    }
+}
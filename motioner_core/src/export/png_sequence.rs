@@ -0,0 +1,173 @@
+//! PNG image-sequence export: `<dir>/frame_00001.png`, `frame_00002.png`, ...
+
+use super::{render_in_order, ExportOutcome, ExportProgress, ExportSettings};
+use crate::scene::Shape;
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+/// File name of frame `index` (0-based); numbering starts at 1.
+pub fn frame_file_name(index: usize) -> String {
+    format!("frame_{:05}.png", index + 1)
+}
+
+/// Render every frame of `scene` into `dir` as numbered PNG files.
+///
+/// Frames left over from an earlier, longer export are removed first so the
+/// folder always holds exactly one sequence.
+pub fn export(
+    scene: &[Shape],
+    settings: &ExportSettings,
+    dir: &Path,
+    progress: &ExportProgress,
+) -> anyhow::Result<ExportOutcome> {
+    export_with(scene, settings, dir, progress, |_| {})
+}
+
+/// [`export`], calling `on_written` with each frame index once its file is
+/// saved.
+fn export_with(
+    scene: &[Shape],
+    settings: &ExportSettings,
+    dir: &Path,
+    progress: &ExportProgress,
+    mut on_written: impl FnMut(usize),
+) -> anyhow::Result<ExportOutcome> {
+    std::fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+    remove_old_frames(dir)?;
+
+    let mut written = 0;
+    let finished = render_in_order(scene, settings, progress, |i, frame| {
+        let path = dir.join(frame_file_name(i));
+        frame
+            .save_with_format(&path, image::ImageFormat::Png)
+            .with_context(|| format!("could not write {}", path.display()))?;
+        written = i + 1;
+        on_written(i);
+        Ok(())
+    })?;
+
+    Ok(if finished {
+        ExportOutcome::Finished {
+            frames: written,
+            path: dir.to_path_buf(),
+        }
+    } else {
+        ExportOutcome::Cancelled { frames: written }
+    })
+}
+
+fn remove_old_frames(dir: &Path) -> anyhow::Result<()> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("could not read {}", dir.display()))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if is_frame_file(&name.to_string_lossy()) {
+            let path: PathBuf = entry.path();
+            std::fs::remove_file(&path)
+                .with_context(|| format!("could not remove {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn is_frame_file(name: &str) -> bool {
+    name.strip_prefix("frame_")
+        .and_then(|rest| rest.strip_suffix(".png"))
+        .is_some_and(|n| n.len() == 5 && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> ExportSettings {
+        ExportSettings {
+            width: 16,
+            height: 8,
+            fps: 10,
+            duration: 1.0,
+        }
+    }
+
+    fn scene() -> Vec<Shape> {
+        crate::dsl::parse_dsl(
+            "rect \"A\" { x = 0.1, y = 0.5, w = 0.2, h = 1, color = \"#ff0000\" }\n\
+             move { element = \"A\", to = (0.9, 0.5), during = 0 -> 1 }\n",
+        )
+    }
+
+    #[test]
+    fn writes_every_frame_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("frame_00042.png"), b"stale").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"keep").unwrap();
+
+        let progress = ExportProgress::default();
+        let outcome = export(&scene(), &settings(), dir.path(), &progress).unwrap();
+        assert_eq!(
+            outcome,
+            ExportOutcome::Finished {
+                frames: 10,
+                path: dir.path().to_path_buf()
+            }
+        );
+        assert_eq!(progress.frames(), (10, 10));
+        assert!(!dir.path().join("frame_00042.png").exists());
+        assert!(dir.path().join("notes.txt").exists());
+
+        // frame 1 is t = 0, frame 10 is t = 0.9: the red bar moves right
+        let first = image::open(dir.path().join("frame_00001.png"))
+            .unwrap()
+            .to_rgba8();
+        let last = image::open(dir.path().join("frame_00010.png"))
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(first.dimensions(), (16, 8));
+        assert_eq!(first.get_pixel(1, 4).0, [255, 0, 0, 255]);
+        assert_eq!(last.get_pixel(1, 4).0[3], 0);
+        assert_eq!(last.get_pixel(13, 4).0, [255, 0, 0, 255]);
+        assert!(!dir.path().join("frame_00011.png").exists());
+    }
+
+    #[test]
+    fn cancelling_stops_early() {
+        let frame_files = |dir: &Path| {
+            std::fs::read_dir(dir)
+                .unwrap()
+                .filter(|e| is_frame_file(&e.as_ref().unwrap().file_name().to_string_lossy()))
+                .count()
+        };
+
+        // cancelled before the first frame
+        let dir = tempfile::tempdir().unwrap();
+        let progress = ExportProgress::default();
+        progress.cancel();
+        let outcome = export(&scene(), &settings(), dir.path(), &progress).unwrap();
+        assert_eq!(outcome, ExportOutcome::Cancelled { frames: 0 });
+        assert_eq!(frame_files(dir.path()), 0);
+
+        // cancelled once frame k is written, in the middle of a batch
+        for k in [1, 3] {
+            let dir = tempfile::tempdir().unwrap();
+            let progress = ExportProgress::default();
+            let outcome = export_with(&scene(), &settings(), dir.path(), &progress, |i| {
+                if i + 1 == k {
+                    progress.cancel();
+                }
+            })
+            .unwrap();
+            assert_eq!(outcome, ExportOutcome::Cancelled { frames: k });
+            assert_eq!(frame_files(dir.path()), k);
+            assert!(dir.path().join(frame_file_name(k - 1)).exists());
+            assert_eq!(progress.frames(), (k, 10));
+        }
+    }
+
+    #[test]
+    fn frame_names_are_one_based_and_padded() {
+        assert_eq!(frame_file_name(0), "frame_00001.png");
+        assert_eq!(frame_file_name(1233), "frame_01234.png");
+        assert!(is_frame_file("frame_00001.png"));
+        assert!(!is_frame_file("frame_1.png"));
+    }
+}
@@ -0,0 +1,417 @@
+//! Video export through an external `ffmpeg` process.
+//!
+//! Frames are rendered like every other export and streamed to ffmpeg's
+//! stdin as raw RGBA, so no intermediate images touch the disk.  ffmpeg's
+//! stderr is collected and becomes the error message when encoding fails.
+
+use super::{render_in_order, ExportOutcome, ExportProgress, ExportSettings};
+use crate::scene::Shape;
+use anyhow::{anyhow, bail, Context};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Encoder presets offered by the export dialog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VideoCodec {
+    /// H.264 in MP4, `yuv420p` for maximum player compatibility.
+    H264,
+    /// VP9 in WebM, keeping the alpha channel.
+    Vp9,
+    /// ProRes 4444 in MOV, keeping the alpha channel.  Quality is fixed by
+    /// the profile, so the rate control setting is ignored.
+    ProRes,
+}
+
+impl VideoCodec {
+    pub fn label(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "MP4 (H.264)",
+            VideoCodec::Vp9 => "WebM (VP9)",
+            VideoCodec::ProRes => "MOV (ProRes 4444)",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "mp4",
+            VideoCodec::Vp9 => "webm",
+            VideoCodec::ProRes => "mov",
+        }
+    }
+
+    /// Whether [`RateControl`] has any effect on this codec.
+    pub fn uses_rate_control(self) -> bool {
+        self != VideoCodec::ProRes
+    }
+
+    /// Highest (worst) constant quality value the encoder accepts.
+    pub fn max_crf(self) -> u32 {
+        match self {
+            VideoCodec::H264 => 51,
+            VideoCodec::Vp9 | VideoCodec::ProRes => 63,
+        }
+    }
+}
+
+/// How the encoder trades size for quality.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateControl {
+    /// Constant quality; lower is better (H.264: 0-51, VP9: 0-63).
+    Crf(u32),
+    /// Target bitrate in kbit/s.
+    Bitrate(u32),
+}
+
+/// Encoder options shared by all video presets.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VideoSettings {
+    pub rate: RateControl,
+    /// Explicit ffmpeg executable; `None` searches `PATH`.
+    pub ffmpeg_path: Option<PathBuf>,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            rate: RateControl::Crf(20),
+            ffmpeg_path: None,
+        }
+    }
+}
+
+/// Locate the ffmpeg executable: the configured path if there is one,
+/// otherwise the first `ffmpeg` on `PATH`.
+pub fn find_ffmpeg(configured: Option<&Path>) -> anyhow::Result<PathBuf> {
+    if let Some(path) = configured {
+        if path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+        bail!(
+            "ffmpeg was not found at {} (check the path in the export settings)",
+            path.display()
+        );
+    }
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    find_in_path("ffmpeg", &path_var).ok_or_else(|| {
+        anyhow!(
+            "ffmpeg was not found on PATH. Install ffmpeg or set its location in the export settings."
+        )
+    })
+}
+
+fn find_in_path(program: &str, path_var: &std::ffi::OsStr) -> Option<PathBuf> {
+    let names: &[String] = if cfg!(windows) {
+        &[format!("{}.exe", program)]
+    } else {
+        &[program.to_string()]
+    };
+    std::env::split_paths(path_var)
+        .flat_map(|dir| names.iter().map(move |n| dir.join(n)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Command-line arguments for encoding raw RGBA frames from stdin into
+/// `output`.
+pub fn ffmpeg_args(
+    codec: VideoCodec,
+    video: &VideoSettings,
+    settings: &ExportSettings,
+    output: &Path,
+) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "-y",
+        "-hide_banner",
+        "-loglevel",
+        "error",
+        "-f",
+        "rawvideo",
+        "-pix_fmt",
+        "rgba",
+        "-s",
+        &format!("{}x{}", settings.width, settings.height),
+        "-r",
+        &settings.fps.to_string(),
+        "-i",
+        "-",
+        "-an",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    let codec_args: &[&str] = match codec {
+        // yuv420p needs even dimensions
+        VideoCodec::H264 => &[
+            "-vf",
+            "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+            "-c:v",
+            "libx264",
+            "-preset",
+            "medium",
+            "-pix_fmt",
+            "yuv420p",
+            "-movflags",
+            "+faststart",
+        ],
+        VideoCodec::Vp9 => &["-c:v", "libvpx-vp9", "-pix_fmt", "yuva420p"],
+        VideoCodec::ProRes => &[
+            "-c:v",
+            "prores_ks",
+            "-profile:v",
+            "4",
+            "-pix_fmt",
+            "yuva444p10le",
+        ],
+    };
+    args.extend(codec_args.iter().map(|s| s.to_string()));
+
+    if codec.uses_rate_control() {
+        match (codec, video.rate) {
+            // VP9 only runs in constant quality mode with a zero bitrate
+            (VideoCodec::Vp9, RateControl::Crf(crf)) => {
+                args.extend(["-crf".into(), crf.to_string(), "-b:v".into(), "0".into()])
+            }
+            (_, RateControl::Crf(crf)) => args.extend(["-crf".into(), crf.to_string()]),
+            (_, RateControl::Bitrate(kbps)) => args.extend(["-b:v".into(), format!("{}k", kbps)]),
+        }
+    }
+
+    args.push(output.to_string_lossy().into_owned());
+    args
+}
+
+/// Render `scene` and encode it into `output` with ffmpeg.
+///
+/// On cancel or failure ffmpeg is stopped and the partial file removed.
+pub fn export(
+    scene: &[Shape],
+    settings: &ExportSettings,
+    codec: VideoCodec,
+    video: &VideoSettings,
+    output: &Path,
+    progress: &ExportProgress,
+) -> anyhow::Result<ExportOutcome> {
+    let ffmpeg = find_ffmpeg(video.ffmpeg_path.as_deref())?;
+    if let Some(dir) = output.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("could not create {}", dir.display()))?;
+    }
+
+    let mut child = Command::new(&ffmpeg)
+        .args(ffmpeg_args(codec, video, settings, output))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("could not start {}", ffmpeg.display()))?;
+
+    // Drain stderr on its own thread so a chatty ffmpeg never blocks
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = std::thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text);
+        text
+    });
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let mut written = 0;
+    let streamed = render_in_order(scene, settings, progress, |i, frame| {
+        stdin.write_all(frame.as_raw())?;
+        written = i + 1;
+        Ok(())
+    });
+    // closing stdin tells ffmpeg the stream is over
+    drop(stdin);
+
+    let stream_error = match streamed {
+        Ok(true) => None,
+        Ok(false) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = std::fs::remove_file(output);
+            return Ok(ExportOutcome::Cancelled { frames: written });
+        }
+        Err(err) => Some(err),
+    };
+
+    let status = child.wait().context("could not wait for ffmpeg")?;
+    let log = stderr_reader.join().unwrap_or_default();
+    if !status.success() || stream_error.is_some() {
+        let _ = std::fs::remove_file(output);
+        let log = log.trim();
+        match stream_error {
+            // a write error usually means ffmpeg quit and its stderr says
+            // why; without a reason from ffmpeg, the write error is it
+            Some(err) if log.is_empty() || status.success() => {
+                return Err(err.context(format!(
+                    "could not stream frame {} to ffmpeg ({})",
+                    written + 1,
+                    status
+                )));
+            }
+            _ if log.is_empty() => bail!("ffmpeg failed ({})", status),
+            _ => bail!("ffmpeg failed ({}):\n{}", status, log),
+        }
+    }
+
+    Ok(ExportOutcome::Finished {
+        frames: written,
+        path: output.to_path_buf(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> ExportSettings {
+        ExportSettings {
+            width: 4,
+            height: 2,
+            fps: 5,
+            duration: 1.0,
+        }
+    }
+
+    #[test]
+    fn presets_build_the_expected_arguments() {
+        let out = Path::new("out.webm");
+        let mut video = VideoSettings {
+            rate: RateControl::Crf(31),
+            ffmpeg_path: None,
+        };
+        let args = ffmpeg_args(VideoCodec::Vp9, &video, &settings(), out).join(" ");
+        assert!(args.starts_with(
+            "-y -hide_banner -loglevel error -f rawvideo -pix_fmt rgba -s 4x2 -r 5 -i - -an"
+        ));
+        assert!(args.ends_with("-c:v libvpx-vp9 -pix_fmt yuva420p -crf 31 -b:v 0 out.webm"));
+
+        video.rate = RateControl::Bitrate(8000);
+        let args = ffmpeg_args(VideoCodec::H264, &video, &settings(), out).join(" ");
+        assert!(args.contains("-c:v libx264"));
+        assert!(args.ends_with("-b:v 8000k out.webm"));
+
+        let args = ffmpeg_args(VideoCodec::ProRes, &video, &settings(), out).join(" ");
+        assert!(args.contains("-c:v prores_ks -profile:v 4"));
+        assert!(!args.contains("-b:v"));
+    }
+
+    #[test]
+    fn missing_ffmpeg_is_a_clear_error() {
+        let err = find_ffmpeg(Some(Path::new("/definitely/not/ffmpeg"))).unwrap_err();
+        assert!(err.to_string().contains("/definitely/not/ffmpeg"));
+        assert_eq!(find_in_path("ffmpeg", std::ffi::OsStr::new("")), None);
+    }
+
+    #[cfg(unix)]
+    fn fake_ffmpeg(dir: &Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("ffmpeg");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn streams_raw_frames_to_ffmpeg() {
+        let dir = tempfile::tempdir().unwrap();
+        // the fake copies stdin into the output file (the last argument)
+        let ffmpeg = fake_ffmpeg(dir.path(), "for last; do :; done\ncat > \"$last\"");
+        assert_eq!(
+            find_in_path("ffmpeg", dir.path().as_os_str()),
+            Some(ffmpeg.clone())
+        );
+
+        let video = VideoSettings {
+            ffmpeg_path: Some(ffmpeg),
+            ..VideoSettings::default()
+        };
+        let scene = crate::dsl::parse_dsl("rect \"A\" { x = 0.5, y = 0.5, w = 1, h = 1 }");
+        let output = dir.path().join("export").join("clip.mp4");
+        let progress = ExportProgress::default();
+        let outcome = export(
+            &scene,
+            &settings(),
+            VideoCodec::H264,
+            &video,
+            &output,
+            &progress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            outcome,
+            ExportOutcome::Finished {
+                frames: 5,
+                path: output.clone()
+            }
+        );
+        let bytes = std::fs::read(&output).unwrap();
+        assert_eq!(bytes.len(), 5 * 4 * 2 * 4);
+        assert!(bytes.iter().all(|&b| b == 255));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ffmpeg_errors_carry_its_stderr() {
+        let dir = tempfile::tempdir().unwrap();
+        let ffmpeg = fake_ffmpeg(dir.path(), "echo \"Unknown encoder 'libx264'\" >&2\nexit 1");
+        let video = VideoSettings {
+            ffmpeg_path: Some(ffmpeg),
+            ..VideoSettings::default()
+        };
+        let output = dir.path().join("clip.mp4");
+        let err = export(
+            &[],
+            &settings(),
+            VideoCodec::H264,
+            &video,
+            &output,
+            &ExportProgress::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.starts_with("ffmpeg failed"), "{}", err);
+        assert!(err.contains("Unknown encoder 'libx264'"), "{}", err);
+        assert!(!output.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stream_errors_are_kept_when_ffmpeg_gives_no_reason() {
+        let dir = tempfile::tempdir().unwrap();
+        // exits cleanly without reading; frames larger than a pipe buffer
+        // make the first write fail once it is gone
+        let ffmpeg = fake_ffmpeg(dir.path(), "exit 0");
+        let video = VideoSettings {
+            ffmpeg_path: Some(ffmpeg),
+            ..VideoSettings::default()
+        };
+        let settings = ExportSettings {
+            width: 256,
+            height: 256,
+            ..settings()
+        };
+        let output = dir.path().join("clip.mp4");
+        let err = export(
+            &[],
+            &settings,
+            VideoCodec::H264,
+            &video,
+            &output,
+            &ExportProgress::default(),
+        )
+        .unwrap_err();
+        let err = format!("{:#}", err);
+        assert!(
+            err.starts_with("could not stream frame 1 to ffmpeg (exit status: 0)"),
+            "{}",
+            err
+        );
+        assert!(err.to_lowercase().contains("pipe"), "{}", err);
+        assert!(!output.exists());
+    }
+}
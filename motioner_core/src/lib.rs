@@ -0,0 +1,14 @@
+//! The headless half of Motioner: the DSL, the scene model, sampling,
+//! timing, rendering, the exporters and the project files.
+//!
+//! Nothing in this crate depends on egui, eframe or winit, so it can be
+//! embedded in other tools.  The Motioner editor and the `motioner`
+//! command-line tool are both built on top of it.
+
+pub mod dsl;
+pub mod export;
+pub mod project;
+pub mod renderer;
+pub mod sampling;
+pub mod scene;
+pub mod timing;
@@ -0,0 +1,371 @@
+//! Project folders and their `motioner.project` manifest.
+//!
+//! A project is a folder holding a JSON manifest next to its DSL entry file
+//! and an assets folder:
+//!
+//! ```text
+//! my-project/
+//!   motioner.project   settings, entry file, assets folder, export presets
+//!   code.motioner      the entry DSL file
+//!   assets/
+//!   export/            exporter output
+//! ```
+//!
+//! The DSL header (`size`, `timeline`) is authoritative for the settings;
+//! the manifest keeps a copy so tools can read them without parsing DSL.
+
+use crate::dsl::ast::HeaderConfig;
+use crate::export::video::{VideoCodec, VideoSettings};
+use crate::export::{ExportFormat, LoopSettings};
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// File name of the manifest inside a project folder.
+pub const MANIFEST_FILE: &str = "motioner.project";
+/// Entry file used by projects without a manifest.
+pub const DEFAULT_ENTRY: &str = "code.motioner";
+/// Manifest format written by this version.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// A named set of export options.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportPreset {
+    pub name: String,
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub video: VideoSettings,
+    #[serde(default)]
+    pub looping: LoopSettings,
+}
+
+impl ExportPreset {
+    pub fn new(name: impl Into<String>, format: ExportFormat) -> Self {
+        Self {
+            name: name.into(),
+            format,
+            video: VideoSettings::default(),
+            looping: LoopSettings::default(),
+        }
+    }
+}
+
+/// Contents of `motioner.project`.  Missing fields take their defaults, so
+/// hand-written manifests can stay short.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectManifest {
+    pub version: u32,
+    pub name: String,
+    pub settings: HeaderConfig,
+    /// DSL file the project starts from, relative to the project folder.
+    pub entry: PathBuf,
+    /// Folder for images and other assets, relative to the project folder.
+    pub assets_dir: PathBuf,
+    pub export_presets: Vec<ExportPreset>,
+}
+
+impl Default for ProjectManifest {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            name: String::new(),
+            settings: HeaderConfig::default(),
+            entry: PathBuf::from(DEFAULT_ENTRY),
+            assets_dir: PathBuf::from("assets"),
+            export_presets: vec![
+                ExportPreset::new("PNG frames", ExportFormat::PngSequence),
+                ExportPreset::new("GIF loop", ExportFormat::Gif),
+                ExportPreset::new("MP4", ExportFormat::Video(VideoCodec::H264)),
+            ],
+        }
+    }
+}
+
+impl ProjectManifest {
+    /// Read the manifest of `dir`; `Ok(None)` when the folder has none.
+    pub fn load(dir: &Path) -> anyhow::Result<Option<Self>> {
+        let path = dir.join(MANIFEST_FILE);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read {}", path.display()))
+            }
+        };
+        let manifest: Self = serde_json::from_str(&text)
+            .with_context(|| format!("{} is not a valid project manifest", path.display()))?;
+        if manifest.version > MANIFEST_VERSION {
+            bail!(
+                "{} was written by a newer Motioner (manifest version {})",
+                path.display(),
+                manifest.version
+            );
+        }
+        Ok(Some(manifest))
+    }
+
+    /// Write the manifest into `dir`.
+    pub fn save(&self, dir: &Path) -> anyhow::Result<()> {
+        let path = dir.join(MANIFEST_FILE);
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        write_atomic(&path, text.as_bytes())
+            .with_context(|| format!("could not write {}", path.display()))
+    }
+
+    /// Add `preset`, whose name must be non-empty and not used by another
+    /// preset.
+    pub fn add_export_preset(&mut self, preset: ExportPreset) -> anyhow::Result<()> {
+        if preset.name.trim().is_empty() {
+            bail!("a preset needs a name");
+        }
+        if self.export_presets.iter().any(|p| p.name == preset.name) {
+            bail!("there is already a preset named \"{}\"", preset.name);
+        }
+        self.export_presets.push(preset);
+        Ok(())
+    }
+
+    pub fn entry_path(&self, dir: &Path) -> PathBuf {
+        dir.join(&self.entry)
+    }
+
+    pub fn assets_path(&self, dir: &Path) -> PathBuf {
+        dir.join(&self.assets_dir)
+    }
+}
+
+/// A loaded project: its folder, manifest and entry source.
+#[derive(Clone, Debug)]
+pub struct Project {
+    pub dir: PathBuf,
+    pub manifest: ProjectManifest,
+    pub source: String,
+}
+
+impl Project {
+    /// Load an existing project without writing anything.  `path` is a
+    /// project folder, or a single DSL file (which gets a default manifest
+    /// in memory).
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let (dir, manifest) = if path.is_dir() {
+            let manifest = ProjectManifest::load(path)?.unwrap_or_default();
+            (path.to_path_buf(), manifest)
+        } else {
+            let dir = match path.parent() {
+                Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            let manifest = ProjectManifest {
+                entry: PathBuf::from(path.file_name().unwrap_or_default()),
+                ..ProjectManifest::default()
+            };
+            (dir, manifest)
+        };
+        let entry = manifest.entry_path(&dir);
+        let source = std::fs::read_to_string(&entry)
+            .with_context(|| format!("could not read {}", entry.display()))?;
+        Ok(Self {
+            dir,
+            manifest,
+            source,
+        })
+    }
+
+    /// Open the project folder `dir`, creating whatever is missing: the
+    /// manifest (named after the folder, with settings taken from the entry
+    /// file's header or `defaults`), the entry file (from `new_source`) and
+    /// the assets folder.
+    pub fn open_or_create(
+        dir: &Path,
+        defaults: &HeaderConfig,
+        new_source: impl FnOnce(&HeaderConfig) -> String,
+    ) -> anyhow::Result<Self> {
+        if !dir.is_dir() {
+            bail!("{} is not a folder", dir.display());
+        }
+        let existing = ProjectManifest::load(dir)?;
+        let mut manifest = existing.clone().unwrap_or_else(|| ProjectManifest {
+            name: dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            settings: defaults.clone(),
+            ..ProjectManifest::default()
+        });
+
+        let entry = manifest.entry_path(dir);
+        let source = match std::fs::read_to_string(&entry) {
+            Ok(source) => source,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                if let Some(parent) = entry.parent() {
+                    std::fs::create_dir_all(parent)
+                        .with_context(|| format!("could not create {}", parent.display()))?;
+                }
+                let source = new_source(&manifest.settings);
+                write_atomic(&entry, source.as_bytes())
+                    .with_context(|| format!("could not create {}", entry.display()))?;
+                source
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read {}", entry.display()))
+            }
+        };
+        if let Ok(header) = crate::dsl::parse_config(&source) {
+            manifest.settings = header;
+        }
+        if existing.as_ref() != Some(&manifest) {
+            manifest.save(dir)?;
+        }
+
+        let assets = manifest.assets_path(dir);
+        std::fs::create_dir_all(&assets)
+            .with_context(|| format!("could not create {}", assets.display()))?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            manifest,
+            source,
+        })
+    }
+
+    pub fn entry_path(&self) -> PathBuf {
+        self.manifest.entry_path(&self.dir)
+    }
+}
+
+/// Replace `path` with `contents` without ever leaving a half-written file:
+/// the data goes to a temporary file in the same folder, which is then
+/// renamed over `path`.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut tmp = tempfile::Builder::new()
+        .prefix(".motioner-save")
+        .suffix(".tmp")
+        .tempfile_in(dir)?;
+    tmp.write_all(contents)?;
+    if let Ok(meta) = std::fs::metadata(path) {
+        tmp.as_file().set_permissions(meta.permissions())?;
+    }
+    tmp.as_file().sync_all()?;
+    tmp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_source(settings: &HeaderConfig) -> String {
+        format!(
+            "size({}, {})\ntimeline(fps = {}, duration = {})\n",
+            settings.width, settings.height, settings.fps, settings.duration
+        )
+    }
+
+    #[test]
+    fn creates_missing_project_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let defaults = HeaderConfig {
+            width: 320,
+            height: 240,
+            fps: 12,
+            duration: 2.0,
+        };
+        let project = Project::open_or_create(dir.path(), &defaults, new_source).unwrap();
+
+        assert_eq!(project.source, new_source(&defaults));
+        assert!(dir.path().join(DEFAULT_ENTRY).is_file());
+        assert!(dir.path().join("assets").is_dir());
+        let manifest = ProjectManifest::load(dir.path()).unwrap().unwrap();
+        assert_eq!(manifest, project.manifest);
+        assert_eq!(manifest.settings, defaults);
+        assert_eq!(
+            Some(manifest.name.as_str()),
+            dir.path().file_name().and_then(|n| n.to_str())
+        );
+    }
+
+    #[test]
+    fn creates_an_entry_in_a_missing_subfolder() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(MANIFEST_FILE),
+            r#"{ "entry": "scenes/intro/main.motioner" }"#,
+        )
+        .unwrap();
+        let project =
+            Project::open_or_create(dir.path(), &HeaderConfig::default(), new_source).unwrap();
+        let entry = dir.path().join("scenes/intro/main.motioner");
+        assert_eq!(project.entry_path(), entry);
+        assert_eq!(std::fs::read_to_string(entry).unwrap(), project.source);
+    }
+
+    #[test]
+    fn opens_existing_projects_and_follows_the_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(MANIFEST_FILE),
+            r#"{ "name": "Intro", "entry": "scenes/main.motioner" }"#,
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("scenes")).unwrap();
+        let source = "size(100, 50)\ntimeline(fps = 30, duration = 1)\n";
+        std::fs::write(dir.path().join("scenes/main.motioner"), source).unwrap();
+
+        let project =
+            Project::open_or_create(dir.path(), &HeaderConfig::default(), |_| unreachable!())
+                .unwrap();
+        assert_eq!(project.source, source);
+        assert_eq!(project.manifest.name, "Intro");
+        // settings follow the entry file's header and are written back
+        assert_eq!(
+            (
+                project.manifest.settings.width,
+                project.manifest.settings.fps
+            ),
+            (100, 30)
+        );
+        let saved = ProjectManifest::load(dir.path()).unwrap().unwrap();
+        assert_eq!(saved.settings, project.manifest.settings);
+        assert_eq!(saved.export_presets.len(), 3);
+
+        let loaded = Project::load(dir.path()).unwrap();
+        assert_eq!(loaded.entry_path(), dir.path().join("scenes/main.motioner"));
+        let single = Project::load(&dir.path().join("scenes/main.motioner")).unwrap();
+        assert_eq!(single.dir, dir.path().join("scenes"));
+    }
+
+    #[test]
+    fn preset_names_are_unique() {
+        let mut manifest = ProjectManifest::default();
+        let mut gif = ExportPreset::new("GIF small", ExportFormat::Gif);
+        gif.looping.loop_count = 2;
+        manifest.add_export_preset(gif.clone()).unwrap();
+        assert_eq!(manifest.export_presets.last(), Some(&gif));
+
+        let err = manifest
+            .add_export_preset(ExportPreset::new("GIF small", ExportFormat::Gif))
+            .unwrap_err();
+        assert!(err.to_string().contains("already a preset"), "{}", err);
+        assert!(manifest
+            .add_export_preset(ExportPreset::new("  ", ExportFormat::Gif))
+            .is_err());
+        assert_eq!(manifest.export_presets.len(), 4);
+    }
+
+    #[test]
+    fn rejects_manifests_from_newer_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(MANIFEST_FILE), r#"{ "version": 99 }"#).unwrap();
+        let err = ProjectManifest::load(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("newer Motioner"), "{}", err);
+        std::fs::write(dir.path().join(MANIFEST_FILE), "not json").unwrap();
+        assert!(ProjectManifest::load(dir.path()).is_err());
+    }
+}
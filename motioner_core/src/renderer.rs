@@ -0,0 +1,149 @@
+//! Headless CPU renderer.
+//!
+//! Rasterizes a scene with tiny-skia into an `image::RgbaImage`.  Nothing
+//! here touches egui, winit or the GPU, so frames can be rendered in tests,
+//! by the exporters and on CI machines without a display.
+//!
+//! Shape coordinates are normalized: `x`/`y` place the shape's centre,
+//! `w`/`h` its size, as fractions of the frame width and height.  The frame
+//! starts out transparent.
+
+use crate::sampling::{sample_scene, ResolvedScene, ResolvedShape};
+use crate::scene::Shape;
+use image::RgbaImage;
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Rect, Transform};
+
+/// Render `scene` as it looks at `time` seconds on a timeline played at
+/// `fps` into a `width` x `height` image with straight (non-premultiplied)
+/// alpha.
+pub fn render_frame(scene: &[Shape], time: f32, fps: u32, width: u32, height: u32) -> RgbaImage {
+    render_resolved(&sample_scene(scene, time, fps), width, height)
+}
+
+/// Render an already sampled scene.
+pub fn render_resolved(scene: &ResolvedScene, width: u32, height: u32) -> RgbaImage {
+    let Some(mut pixmap) = Pixmap::new(width, height) else {
+        // zero-sized frame
+        return RgbaImage::new(width, height);
+    };
+    let (fw, fh) = (width as f32, height as f32);
+
+    for shape in &scene.shapes {
+        match shape {
+            ResolvedShape::Rect {
+                x, y, w, h, color, ..
+            } => {
+                let (pw, ph) = (w * fw, h * fh);
+                let Some(rect) = Rect::from_xywh(x * fw - pw / 2.0, y * fh - ph / 2.0, pw, ph)
+                else {
+                    continue;
+                };
+                let path = PathBuilder::from_rect(rect);
+                pixmap.fill_path(
+                    &path,
+                    &paint(*color),
+                    FillRule::Winding,
+                    Transform::identity(),
+                    None,
+                );
+            }
+        }
+    }
+
+    to_rgba_image(&pixmap)
+}
+
+/// A solid anti-aliased paint.  tiny-skia takes straight alpha here and
+/// premultiplies internally.
+fn paint(color: [u8; 4]) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(color[0], color[1], color[2], color[3]);
+    paint.anti_alias = true;
+    paint
+}
+
+/// Convert tiny-skia's premultiplied pixels into straight-alpha RGBA.
+fn to_rgba_image(pixmap: &Pixmap) -> RgbaImage {
+    let mut out = Vec::with_capacity(pixmap.data().len());
+    for px in pixmap.pixels() {
+        let c = px.demultiply();
+        out.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+    }
+    RgbaImage::from_raw(pixmap.width(), pixmap.height(), out)
+        .expect("pixmap buffer matches its dimensions")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(name: &str, x: f32, y: f32, w: f32, h: f32, color: [u8; 4]) -> Shape {
+        Shape::Rect {
+            name: name.to_string(),
+            x: x.into(),
+            y: y.into(),
+            w: w.into(),
+            h: h.into(),
+            color,
+            animations: vec![],
+        }
+    }
+
+    #[test]
+    fn maps_normalized_coordinates_to_pixels() {
+        // 40x20 px rect centred in a 100x50 frame covers x 30..70, y 15..35
+        let scene = [rect("A", 0.5, 0.5, 0.4, 0.4, [255, 0, 0, 255])];
+        let img = render_frame(&scene, 0.0, 60, 100, 50);
+        assert_eq!(img.dimensions(), (100, 50));
+        assert_eq!(img.get_pixel(30, 15).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(69, 34).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(29, 25).0[3], 0);
+        assert_eq!(img.get_pixel(70, 25).0[3], 0);
+        assert_eq!(img.get_pixel(50, 14).0[3], 0);
+    }
+
+    #[test]
+    fn edges_are_anti_aliased() {
+        // left edge at x = 10.5 px: the pixel column 10 is half covered
+        let scene = [rect("A", 0.5, 0.5, 0.79, 1.0, [0, 255, 0, 255])];
+        let img = render_frame(&scene, 0.0, 60, 100, 10);
+        let edge = img.get_pixel(10, 5).0;
+        assert!((100..=156).contains(&edge[3]), "{:?}", edge);
+        // straight alpha: the color stays pure green at partial coverage
+        assert_eq!(&edge[..3], &[0, 255, 0]);
+    }
+
+    #[test]
+    fn translucent_shapes_blend_with_premultiplied_alpha() {
+        let scene = [
+            rect("Back", 0.5, 0.5, 1.0, 1.0, [0, 0, 255, 255]),
+            rect("Front", 0.5, 0.5, 1.0, 1.0, [255, 0, 0, 128]),
+            rect("Alone", 0.5, 0.5, 0.0, 0.0, [0, 0, 0, 255]),
+        ];
+        let img = render_frame(&scene, 0.0, 60, 4, 4);
+        let [r, g, b, a] = img.get_pixel(1, 1).0;
+        assert_eq!((g, a), (0, 255));
+        assert!((r as i32 - 128).abs() <= 1 && (b as i32 - 127).abs() <= 1);
+
+        // a lone translucent shape keeps its straight color and alpha
+        let img = render_frame(&scene[1..2], 0.0, 60, 4, 4);
+        let [r, g, b, a] = img.get_pixel(1, 1).0;
+        assert_eq!((g, b, a), (0, 0, 128));
+        assert!(r >= 254);
+    }
+
+    #[test]
+    fn renders_the_scene_at_the_requested_time() {
+        let scene = crate::dsl::parse_dsl(
+            "rect \"A\" { x = 0.25, y = 0.5, w = 0.1, h = 0.1, color = \"#ffffff\" }\n\
+             move { element = \"A\", to = (0.75, 0.5), during = 0 -> 1 }\n",
+        );
+        let start = render_frame(&scene, 0.0, 60, 20, 20);
+        let end = render_frame(&scene, 1.0, 60, 20, 20);
+        assert_eq!(start.get_pixel(5, 10).0[3], 255);
+        assert_eq!(start.get_pixel(15, 10).0[3], 0);
+        assert_eq!(end.get_pixel(5, 10).0[3], 0);
+        assert_eq!(end.get_pixel(15, 10).0[3], 255);
+        assert_eq!(render_frame(&scene, 0.0, 60, 0, 10).dimensions(), (0, 10));
+    }
+}
@@ -0,0 +1,674 @@
+use crate::dsl::evaluator::{self, CompiledExpr, EvalContext, EvalError};
+use crate::dsl::utils::{format_hex_color, quote_name};
+use serde::{Deserialize, Serialize};
+
+/// Maps animation progress `t` in `0..=1` to eased progress.  Curves named
+/// after CSS or easings.net follow those definitions exactly.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// Hold the start value, jump to the end value when the animation ends.
+    Step,
+    EaseIn {
+        power: f32,
+    },
+    EaseOut {
+        power: f32,
+    },
+    EaseInOut {
+        power: f32,
+    },
+    /// CSS `cubic-bezier(x1, y1, x2, y2)`; `x1` and `x2` lie in `0..=1`.
+    CubicBezier {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+    },
+    /// CSS `steps(count, jump-start | jump-end)`.
+    Steps {
+        count: u32,
+        jump: StepJump,
+    },
+    /// Overshooting curve (easings.net `easeInBack` and friends).
+    Back {
+        mode: EaseMode,
+        overshoot: f32,
+    },
+    /// Decaying sine wave (Penner's elastic with amplitude and period).
+    Elastic {
+        mode: EaseMode,
+        amplitude: f32,
+        period: f32,
+    },
+    /// easings.net `easeOutBounce` and its mirror images.
+    Bounce {
+        mode: EaseMode,
+    },
+    /// A damped spring released from rest at 0 and settling on 1.
+    Spring {
+        stiffness: f32,
+        damping: f32,
+        mass: f32,
+    },
+}
+
+/// Which end of the animation a curve family is applied to.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EaseMode {
+    In,
+    Out,
+    InOut,
+}
+
+/// Where the jumps of a [`Easing::Steps`] curve happen.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StepJump {
+    Start,
+    End,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Animation {
+    Move {
+        to_x: f32,
+        to_y: f32,
+        start: f32,
+        end: f32,
+        easing: Easing,
+    },
+}
+
+/// A numeric shape property: either a literal, or an expression such as
+/// `0.5 + 0.2 * sin(seconds * tau)` that is evaluated again at every
+/// sampled time (see [`crate::sampling`]).  Serialized as a number or as
+/// the expression's source text.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Property {
+    Value(f32),
+    Expr(PropertyExpr),
+}
+
+/// A compiled property expression together with its source text, which is
+/// what `to_dsl` writes back and what serialization keeps.  The values of
+/// `let` bindings and loop variables are written into the source in place
+/// of their names (`x = pad * 2` keeps `0.05 * 2`), so it needs neither to
+/// be read back.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PropertyExpr {
+    source: String,
+    compiled: CompiledExpr,
+}
+
+impl PropertyExpr {
+    pub fn new(source: &str) -> Result<Self, EvalError> {
+        Ok(Self {
+            source: source.to_string(),
+            compiled: evaluator::compile(source)?,
+        })
+    }
+
+    /// An expression already compiled from `source`, e.g. with bindings
+    /// folded in (see [`CompiledExpr::bind`]).
+    pub fn from_compiled(source: &str, compiled: CompiledExpr) -> Self {
+        Self {
+            source: source.to_string(),
+            compiled,
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn compiled(&self) -> &CompiledExpr {
+        &self.compiled
+    }
+}
+
+impl TryFrom<String> for PropertyExpr {
+    type Error = EvalError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::new(&source)
+    }
+}
+
+impl From<PropertyExpr> for String {
+    fn from(expr: PropertyExpr) -> Self {
+        expr.source
+    }
+}
+
+impl Property {
+    /// The value at the time described by `ctx`.  An expression that fails
+    /// there (a division by zero, a boolean result) evaluates to 0.
+    pub fn eval(&self, ctx: &EvalContext) -> f32 {
+        match self {
+            Property::Value(v) => *v,
+            Property::Expr(expr) => expr.compiled.eval_number(ctx).unwrap_or(0.0),
+        }
+    }
+
+    /// The literal value, `None` for an expression.
+    pub fn value(&self) -> Option<f32> {
+        match self {
+            Property::Value(v) => Some(*v),
+            Property::Expr(_) => None,
+        }
+    }
+
+    /// DSL spelling: literals in the shortest form that reads back exactly,
+    /// expressions as written.
+    pub fn to_dsl(&self) -> String {
+        match self {
+            Property::Value(v) => v.to_string(),
+            Property::Expr(expr) => expr.source.clone(),
+        }
+    }
+}
+
+impl From<f32> for Property {
+    fn from(v: f32) -> Self {
+        Property::Value(v)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Shape {
+    Rect {
+        name: String,
+        x: Property,
+        y: Property,
+        w: Property,
+        h: Property,
+        color: [u8; 4],
+        /// Applied in list order; see `docs/src/reference/animations`.
+        #[serde(default)]
+        animations: Vec<Animation>,
+    },
+}
+
+impl Easing {
+    /// Eased progress for `t`, which is clamped to `0..=1`.  The result is
+    /// exactly 0 at `t = 0` and 1 at `t = 1` (except `Steps` with
+    /// `jump_start`, which has already jumped at 0) but may leave `0..=1`
+    /// in between for back, elastic, spring and some bezier curves.
+    pub fn evaluate(&self, t: f32) -> f32 {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        match self {
+            Easing::Linear => t,
+            Easing::Step => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Easing::EaseIn { power } => apply_mode(EaseMode::In, t, |t| t.powf(*power)),
+            Easing::EaseOut { power } => apply_mode(EaseMode::Out, t, |t| t.powf(*power)),
+            Easing::EaseInOut { power } => apply_mode(EaseMode::InOut, t, |t| t.powf(*power)),
+            Easing::CubicBezier { x1, y1, x2, y2 } => cubic_bezier(*x1, *y1, *x2, *y2, t),
+            Easing::Steps { count, jump } => {
+                let n = (*count).max(1) as f32;
+                let mut step = (t * n).floor();
+                if *jump == StepJump::Start {
+                    step += 1.0;
+                }
+                (step / n).min(1.0)
+            }
+            Easing::Back { mode, overshoot } => {
+                let c1 = *overshoot;
+                apply_mode(*mode, t, |t| (c1 + 1.0) * t * t * t - c1 * t * t)
+            }
+            Easing::Elastic {
+                mode,
+                amplitude,
+                period,
+            } => apply_mode(*mode, t, |t| elastic_in(t, *amplitude, *period)),
+            Easing::Bounce { mode } => apply_mode(*mode, t, |t| 1.0 - bounce_out(1.0 - t)),
+            Easing::Spring {
+                stiffness,
+                damping,
+                mass,
+            } => spring(*stiffness, *damping, *mass, t),
+        }
+    }
+
+    /// DSL spelling of this easing, as accepted by `ease = ...`.
+    pub fn to_dsl(&self) -> String {
+        let family = |mode: &EaseMode, name: &str| match mode {
+            EaseMode::In => format!("ease_in_{}", name),
+            EaseMode::Out => format!("ease_out_{}", name),
+            EaseMode::InOut => format!("ease_in_out_{}", name),
+        };
+        match self {
+            Easing::Linear => "linear".to_string(),
+            Easing::Step => "step".to_string(),
+            Easing::EaseIn { power } => format!("ease_in(power = {})", power),
+            Easing::EaseOut { power } => format!("ease_out(power = {})", power),
+            Easing::EaseInOut { power } => format!("ease_in_out(power = {})", power),
+            Easing::CubicBezier { x1, y1, x2, y2 } => {
+                format!("cubic_bezier({}, {}, {}, {})", x1, y1, x2, y2)
+            }
+            Easing::Steps { count, jump } => format!(
+                "steps({}, {})",
+                count,
+                match jump {
+                    StepJump::Start => "jump_start",
+                    StepJump::End => "jump_end",
+                }
+            ),
+            Easing::Back { mode, overshoot } => {
+                format!("{}(overshoot = {})", family(mode, "back"), overshoot)
+            }
+            Easing::Elastic {
+                mode,
+                amplitude,
+                period,
+            } => format!(
+                "{}(amplitude = {}, period = {})",
+                family(mode, "elastic"),
+                amplitude,
+                period
+            ),
+            Easing::Bounce { mode } => family(mode, "bounce"),
+            Easing::Spring {
+                stiffness,
+                damping,
+                mass,
+            } => format!(
+                "spring(stiffness = {}, damping = {}, mass = {})",
+                stiffness, damping, mass
+            ),
+        }
+    }
+}
+
+/// Build the out / in-out variants of a curve from its "in" form: "out" is
+/// the in-curve rotated 180°, "in-out" runs the in-curve over the first half
+/// and the out-curve over the second.
+fn apply_mode(mode: EaseMode, t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    match mode {
+        EaseMode::In => ease_in(t),
+        EaseMode::Out => 1.0 - ease_in(1.0 - t),
+        EaseMode::InOut => {
+            if t < 0.5 {
+                ease_in(2.0 * t) / 2.0
+            } else {
+                1.0 - ease_in(2.0 - 2.0 * t) / 2.0
+            }
+        }
+    }
+}
+
+/// Solve the bezier's x(s) = t for s (Newton's method, falling back to
+/// bisection where the slope is too flat) and return y(s).
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, t: f32) -> f32 {
+    // Coefficients of the polynomial form a*s^3 + b*s^2 + c*s, in f64 so
+    // the solver converges well within f32 precision.
+    let coeffs = |p1: f32, p2: f32| {
+        let c = 3.0 * p1 as f64;
+        let b = 3.0 * (p2 as f64 - p1 as f64) - c;
+        (1.0 - c - b, b, c)
+    };
+    let (ax, bx, cx) = coeffs(x1, x2);
+    let (ay, by, cy) = coeffs(y1, y2);
+    let x_at = |s: f64| ((ax * s + bx) * s + cx) * s;
+    let t = t as f64;
+
+    let mut s = t;
+    for _ in 0..8 {
+        let err = x_at(s) - t;
+        if err.abs() < 1e-7 {
+            return (((ay * s + by) * s + cy) * s) as f32;
+        }
+        let slope = (3.0 * ax * s + 2.0 * bx) * s + cx;
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s -= err / slope;
+    }
+
+    let (mut lo, mut hi) = (0.0, 1.0);
+    s = t;
+    for _ in 0..60 {
+        let x = x_at(s);
+        if (x - t).abs() < 1e-7 {
+            break;
+        }
+        if x < t {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) / 2.0;
+    }
+    (((ay * s + by) * s + cy) * s) as f32
+}
+
+fn elastic_in(t: f32, amplitude: f32, period: f32) -> f32 {
+    if t <= 0.0 || t >= 1.0 {
+        return t;
+    }
+    let a = amplitude.max(1.0);
+    let p = period.max(f32::EPSILON);
+    let s = p / std::f32::consts::TAU * (1.0 / a).asin();
+    -(a * 2f32.powf(10.0 * (t - 1.0)) * ((t - 1.0 - s) * std::f32::consts::TAU / p).sin())
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+/// Position of a unit mass-spring-damper released from rest at 0 with its
+/// rest point at 1.  The normalized `t` is stretched over the time the
+/// spring needs to settle within 0.1% of its target, so the animation's
+/// `during` range sets how fast the spring plays.
+fn spring(stiffness: f32, damping: f32, mass: f32, t: f32) -> f32 {
+    if t >= 1.0 {
+        return 1.0;
+    }
+    let k = stiffness.max(f32::EPSILON) as f64;
+    let m = mass.max(f32::EPSILON) as f64;
+    let c = damping.max(0.0) as f64;
+    let w0 = (k / m).sqrt();
+    let zeta = c / (2.0 * (k * m).sqrt());
+
+    // Slowest decay rate of the displacement envelope
+    let decay = if zeta < 1.0 {
+        zeta * w0
+    } else {
+        w0 * (zeta - (zeta * zeta - 1.0).sqrt())
+    };
+    let settle = if decay > 0.0 {
+        1000f64.ln() / decay
+    } else {
+        // undamped: play a single period
+        std::f64::consts::TAU / w0
+    };
+    spring_position(w0, zeta, t as f64 * settle) as f32
+}
+
+fn spring_position(w0: f64, zeta: f64, time: f64) -> f64 {
+    if zeta < 1.0 {
+        let wd = w0 * (1.0 - zeta * zeta).sqrt();
+        1.0 - (-zeta * w0 * time).exp() * ((wd * time).cos() + zeta * w0 / wd * (wd * time).sin())
+    } else if zeta == 1.0 {
+        1.0 - (-w0 * time).exp() * (1.0 + w0 * time)
+    } else {
+        let root = (zeta * zeta - 1.0).sqrt();
+        let r1 = -w0 * (zeta - root);
+        let r2 = -w0 * (zeta + root);
+        1.0 - (r2 * (r1 * time).exp() - r1 * (r2 * time).exp()) / (r2 - r1)
+    }
+}
+
+impl Animation {
+    /// The animation as a block nested inside its shape's `animations { }`.
+    pub fn to_dsl(&self, indent: &str) -> String {
+        match self {
+            Animation::Move {
+                to_x,
+                to_y,
+                start,
+                end,
+                easing,
+            } => format!(
+                "{i}move {{\n{i}\tto = ({}, {}),\n{i}\tduring = {} -> {},\n{i}\tease = {},\n{i}}}\n",
+                to_x,
+                to_y,
+                start,
+                end,
+                easing.to_dsl(),
+                i = indent
+            ),
+        }
+    }
+}
+
+impl Shape {
+    pub fn name(&self) -> &str {
+        match self {
+            Shape::Rect { name, .. } => name,
+        }
+    }
+
+    pub fn animations(&self) -> &[Animation] {
+        match self {
+            Shape::Rect { animations, .. } => animations,
+        }
+    }
+
+    pub fn animations_mut(&mut self) -> &mut Vec<Animation> {
+        match self {
+            Shape::Rect { animations, .. } => animations,
+        }
+    }
+
+    pub fn to_dsl(&self, indent: &str) -> String {
+        match self {
+            Shape::Rect {
+                name,
+                x,
+                y,
+                w,
+                h,
+                color,
+                animations,
+            } => {
+                let mut anims = String::new();
+                if !animations.is_empty() {
+                    let inner = format!("{}\t\t", indent);
+                    anims.push_str(&format!("\t{}animations {{\n", indent));
+                    for anim in animations {
+                        anims.push_str(&anim.to_dsl(&inner));
+                    }
+                    anims.push_str(&format!("\t{}}}\n", indent));
+                }
+                format!(
+                    "{}rect {} {{\n\t{}x = {},\n\t{}y = {},\n\t{}w = {},\n\t{}h = {},\n\t{}color = \"{}\",\n{}{}}}\n",
+                    indent,
+                    quote_name(name),
+                    indent,
+                    x.to_dsl(),
+                    indent,
+                    y.to_dsl(),
+                    indent,
+                    w.to_dsl(),
+                    indent,
+                    h.to_dsl(),
+                    indent,
+                    format_hex_color(*color),
+                    anims,
+                    indent
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_curve(easing: &Easing, reference: impl Fn(f32) -> f32) {
+        for i in 0..=20 {
+            let t = i as f32 / 20.0;
+            let (got, want) = (easing.evaluate(t), reference(t));
+            assert!(
+                (got - want).abs() < 1e-4,
+                "{:?} at t = {}: got {}, want {}",
+                easing,
+                t,
+                got,
+                want
+            );
+        }
+    }
+
+    #[test]
+    fn power_curves_match_easings_net() {
+        assert_curve(&Easing::EaseIn { power: 3.0 }, |t| t * t * t);
+        assert_curve(&Easing::EaseOut { power: 2.0 }, |t| {
+            1.0 - (1.0 - t) * (1.0 - t)
+        });
+        // easeInOutCubic
+        assert_curve(&Easing::EaseInOut { power: 3.0 }, |t| {
+            if t < 0.5 {
+                4.0 * t * t * t
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+            }
+        });
+        assert_curve(&Easing::Linear, |t| t);
+        assert_eq!(Easing::Step.evaluate(0.99), 0.0);
+        assert_eq!(Easing::Step.evaluate(1.0), 1.0);
+    }
+
+    #[test]
+    fn cubic_bezier_matches_css_keywords() {
+        let bezier = |x1, y1, x2, y2| Easing::CubicBezier { x1, y1, x2, y2 };
+        // CSS `ease`, `ease-in-out` and an overshooting curve, checked
+        // against a high-precision bisection of the same bezier
+        let cases = [
+            (bezier(0.25, 0.1, 0.25, 1.0), 0.5, 0.802_403_4),
+            (bezier(0.42, 0.0, 0.58, 1.0), 0.25, 0.129_161_9),
+            (bezier(0.68, -0.6, 0.32, 1.6), 0.3, -0.068_663_4),
+        ];
+        for (easing, t, want) in cases {
+            assert!((easing.evaluate(t) - want).abs() < 1e-5, "{:?}", easing);
+            assert_eq!(easing.evaluate(0.0), 0.0);
+            assert!((easing.evaluate(1.0) - 1.0).abs() < 1e-6);
+        }
+        assert_curve(&bezier(0.0, 0.0, 1.0, 1.0), |t| t);
+    }
+
+    #[test]
+    fn steps_follow_css_jump_terms() {
+        let end = Easing::Steps {
+            count: 4,
+            jump: StepJump::End,
+        };
+        let start = Easing::Steps {
+            count: 4,
+            jump: StepJump::Start,
+        };
+        let at = |e: &Easing| [0.0, 0.2, 0.25, 0.6, 0.99, 1.0].map(|t| e.evaluate(t));
+        assert_eq!(at(&end), [0.0, 0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(at(&start), [0.25, 0.25, 0.5, 0.75, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn back_elastic_and_bounce_match_easings_net() {
+        const C1: f32 = 1.70158;
+        const C3: f32 = C1 + 1.0;
+        const C4: f32 = std::f32::consts::TAU / 3.0;
+        assert_curve(
+            &Easing::Back {
+                mode: EaseMode::In,
+                overshoot: C1,
+            },
+            |t| C3 * t * t * t - C1 * t * t,
+        );
+        assert_curve(
+            &Easing::Back {
+                mode: EaseMode::Out,
+                overshoot: C1,
+            },
+            |t| 1.0 + C3 * (t - 1.0).powi(3) + C1 * (t - 1.0).powi(2),
+        );
+        let elastic = |mode| Easing::Elastic {
+            mode,
+            amplitude: 1.0,
+            period: 0.3,
+        };
+        assert_curve(&elastic(EaseMode::Out), |t| {
+            if t == 0.0 || t == 1.0 {
+                t
+            } else {
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * C4).sin() + 1.0
+            }
+        });
+        assert_curve(&elastic(EaseMode::In), |t| {
+            if t == 0.0 || t == 1.0 {
+                t
+            } else {
+                -(2f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * C4).sin()
+            }
+        });
+        assert_curve(
+            &Easing::Bounce {
+                mode: EaseMode::Out,
+            },
+            bounce_out,
+        );
+        assert_curve(
+            &Easing::Bounce {
+                mode: EaseMode::InOut,
+            },
+            |t| {
+                if t < 0.5 {
+                    (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+                } else {
+                    (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+                }
+            },
+        );
+        assert!((bounce_out(0.5) - 0.765_625).abs() < 1e-6);
+    }
+
+    #[test]
+    fn spring_settles_and_overshoots_only_when_underdamped() {
+        let spring = |damping| Easing::Spring {
+            stiffness: 100.0,
+            damping,
+            mass: 1.0,
+        };
+        let bouncy = spring(5.0);
+        let samples: Vec<f32> = (0..=100)
+            .map(|i| bouncy.evaluate(i as f32 / 100.0))
+            .collect();
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[100], 1.0);
+        assert!(samples.iter().any(|&v| v > 1.2));
+        assert!((samples[99] - 1.0).abs() < 2e-3);
+
+        // zeta = 0.25, w0 = 10, settling over ln(1000) / 2.5 seconds
+        let time = 0.1 * 1000f32.ln() / 2.5;
+        let wd = 10.0 * (1.0f32 - 0.0625).sqrt();
+        let want = 1.0 - (-2.5 * time).exp() * ((wd * time).cos() + 2.5 / wd * (wd * time).sin());
+        assert!((bouncy.evaluate(0.1) - want).abs() < 1e-4);
+
+        for damping in [20.0, 40.0] {
+            let e = spring(damping);
+            let mut prev = 0.0;
+            for i in 0..=100 {
+                let v = e.evaluate(i as f32 / 100.0);
+                assert!(
+                    v >= prev && v <= 1.0,
+                    "damping {}: {} after {}",
+                    damping,
+                    v,
+                    prev
+                );
+                prev = v;
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Timeline arithmetic shared by the editor's playhead and the exporters.
+
+/// A timeline of `duration` seconds played at `fps` frames per second.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timeline {
+    pub fps: u32,
+    pub duration: f32,
+}
+
+impl Timeline {
+    /// Length of one frame in seconds.
+    pub fn frame_duration(&self) -> f32 {
+        1.0 / self.fps.max(1) as f32
+    }
+
+    /// Keep `time` on the timeline.
+    pub fn clamp(&self, time: f32) -> f32 {
+        time.clamp(0.0, self.duration.max(0.0))
+    }
+
+    /// Move `time` by `frames` frames (negative steps back), clamped.
+    pub fn step(&self, time: f32, frames: i32) -> f32 {
+        self.clamp(time + frames as f32 * self.frame_duration())
+    }
+
+    /// Number of frames: one per `1 / fps` seconds of the duration.
+    pub fn frame_count(&self) -> usize {
+        (self.duration.max(0.0) * self.fps as f32).round() as usize
+    }
+
+    /// Time of frame `index` (0-based).
+    pub fn frame_time(&self, index: usize) -> f32 {
+        index as f32 / self.fps.max(1) as f32
+    }
+
+    /// The frame showing at `time`.
+    pub fn frame_at(&self, time: f32) -> usize {
+        (self.clamp(time) * self.fps as f32).round() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_and_counts_frames() {
+        let timeline = Timeline {
+            fps: 24,
+            duration: 2.0,
+        };
+        assert_eq!(timeline.frame_count(), 48);
+        assert_eq!(timeline.frame_time(12), 0.5);
+        assert_eq!(timeline.frame_at(0.5), 12);
+        assert_eq!(timeline.step(0.5, 12), 1.0);
+        assert_eq!(timeline.step(0.0, -1), 0.0);
+        assert_eq!(timeline.step(1.99, 3), 2.0);
+        assert_eq!(timeline.clamp(-1.0), 0.0);
+    }
+}
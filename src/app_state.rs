@@ -1,6 +1,7 @@
 use crate::scene::Shape;
 use crate::states::autosave::AutosaveState;
 use crate::states::dslstate::DslState;
+use crate::states::history::{EditKind, History};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::mpsc::{Receiver, Sender};
@@ -13,7 +14,7 @@ pub enum Tool {
     Text,
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum PanelTab {
     SceneGraph,
     Code,
@@ -34,6 +35,14 @@ pub struct AppState {
     pub scene: Vec<Shape>,
     pub selected: Option<usize>,
     pub selected_node_path: Option<Vec<usize>>,
+    /// Scene graph drag and drop: (shape index, index to insert before).
+    #[serde(skip)]
+    pub move_request: Option<(usize, usize)>,
+    /// Scene graph row being renamed, and the name typed so far.
+    #[serde(skip)]
+    pub renaming: Option<usize>,
+    #[serde(skip)]
+    pub rename_buffer: String,
 
     pub active_tab: Option<PanelTab>,
     pub last_active_tab: PanelTab,
@@ -42,8 +51,21 @@ pub struct AppState {
 
     #[serde(skip)]
     pub autosave: AutosaveState,
+    /// Undo/redo steps of `dsl_code`; the memory limit is remembered with
+    /// the panel layout (see `view_state`).
+    #[serde(default)]
+    pub history: History,
+    /// Polls the entry file for edits made outside the app.
+    #[serde(skip)]
+    pub file_watch: crate::states::file_watch::FileWatchState,
+    /// Layout and per-project views persisted between sessions.
+    #[serde(skip)]
+    pub view_store: crate::states::view_state::ViewStore,
     #[serde(skip)]
     pub last_scene_parse_time: f64,
+    /// Time of the newest DSL edit not yet reflected in `scene`.
+    #[serde(skip)]
+    pub scene_parse_pending: Option<f64>,
 
     // Canvas panning/zoom
     pub canvas_pan_x: f32,
@@ -61,11 +83,25 @@ pub struct AppState {
 
     pub scene_version: u32,
 
+    // Canvas preview
+    #[serde(skip)]
+    pub preview_worker: Option<crate::canvas::preview_worker::PreviewWorker>,
+    #[serde(skip)]
+    pub preview_texture: Option<egui::TextureHandle>,
+    /// Time of the frame currently held in `preview_texture`.
+    #[serde(skip)]
+    pub preview_time: Option<f32>,
+
     pub transport_pos: Option<egui::Pos2>,
 
     pub show_welcome: bool,
     pub project_path_input: String,
     pub project_path: Option<PathBuf>,
+    /// Manifest of the open project.
+    #[serde(skip)]
+    pub project_manifest: Option<crate::project::ProjectManifest>,
+    #[serde(skip)]
+    pub recent_projects: crate::states::recent_projects::RecentProjects,
     pub path_validation_error: Option<String>,
 
     #[serde(skip)]
@@ -83,6 +119,26 @@ pub struct AppState {
     pub settings_is_closing: bool,
     #[serde(skip)]
     pub duration_input_buffer: String,
+
+    // Export dialog
+    #[serde(skip)]
+    pub show_export: bool,
+    #[serde(skip)]
+    pub export_format: crate::export::ExportFormat,
+    /// Rate control and ffmpeg location for video exports.
+    #[serde(default)]
+    pub export_video: crate::export::video::VideoSettings,
+    /// Loop count and dithering for GIF and APNG exports.
+    #[serde(default)]
+    pub export_loop: crate::export::LoopSettings,
+    #[serde(skip)]
+    pub export_job: Option<crate::export::ExportJob>,
+    /// Result of the last finished export, shown in the dialog.
+    #[serde(skip)]
+    pub export_message: Option<String>,
+    /// Name typed for the next saved export preset.
+    #[serde(skip)]
+    pub export_preset_name: String,
 }
 
 impl Default for AppState {
@@ -99,12 +155,19 @@ impl Default for AppState {
             scene: Vec::new(),
             selected: None,
             selected_node_path: None,
+            move_request: None,
+            renaming: None,
+            rename_buffer: String::new(),
             active_tab: Some(PanelTab::Code),
             last_active_tab: PanelTab::Code,
             active_tool: Tool::Select,
             left_panel_width: 400.0,
             autosave: AutosaveState::default(),
+            history: History::default(),
+            file_watch: Default::default(),
+            view_store: Default::default(),
             last_scene_parse_time: 0.0,
+            scene_parse_pending: None,
             canvas_pan_x: 0.0,
             canvas_pan_y: 0.0,
             canvas_zoom: 1.0,
@@ -115,10 +178,15 @@ impl Default for AppState {
             timeline_prev_root_path: None,
             timeline_breadcrumb_anim_t: 1.0,
             scene_version: 1,
+            preview_worker: None,
+            preview_texture: None,
+            preview_time: None,
             transport_pos: None,
             show_welcome: true,
             project_path_input: String::new(),
             project_path: None,
+            project_manifest: None,
+            recent_projects: Default::default(),
             path_validation_error: None,
             folder_dialog_rx: None,
             folder_dialog_tx: None,
@@ -127,6 +195,13 @@ impl Default for AppState {
             settings_open_time: None,
             settings_is_closing: false,
             duration_input_buffer: "5.0".to_string(),
+            show_export: false,
+            export_format: crate::export::ExportFormat::PngSequence,
+            export_video: Default::default(),
+            export_loop: Default::default(),
+            export_job: None,
+            export_message: None,
+            export_preset_name: String::new(),
         }
     }
 }
@@ -134,44 +209,159 @@ impl Default for AppState {
 impl AppState {
     pub fn initialize_with_context(&mut self, _cc: &eframe::CreationContext<'_>) {}
 
+    /// The scene as it looks at the playhead.
+    pub fn resolved_scene(&self) -> crate::sampling::ResolvedScene {
+        crate::sampling::sample_scene(&self.scene, self.time, self.fps)
+    }
+
     pub fn tick(&mut self, now: f64) -> bool {
+        // Autosave clears `last_edit_time` once it has run, so remember the
+        // edit for the (independently debounced) scene parse first.
+        if let Some(edit) = self.autosave.last_edit_time {
+            self.scene_parse_pending = Some(edit);
+        }
+        crate::states::file_watch::tick(self, now);
         crate::states::autosave::tick(self, now);
         self.debounced_parse(now)
     }
 
     pub fn debounced_parse(&mut self, now: f64) -> bool {
         let parse_debounce = 0.12_f64;
-        if let Some(last_edit) = self.autosave.last_edit_time {
+        if let Some(last_edit) = self.scene_parse_pending {
             if now - last_edit > parse_debounce && now - self.last_scene_parse_time > parse_debounce
             {
-                if let Ok(config) = crate::dsl::parse_config(&self.dsl_code) {
-                    self.fps = config.fps;
-                    self.duration_secs = config.duration;
-                    self.render_width = config.width;
-                    self.render_height = config.height;
-                }
-
-                self.scene = crate::dsl::parse_dsl(&self.dsl_code);
-                self.scene_version += 1;
-                self.last_scene_parse_time = now;
+                self.scene_parse_pending = None;
+                self.parse_scene(now);
                 return true;
             }
         }
         false
     }
 
+    /// Re-read the header settings and the scene from `dsl_code`.
+    pub fn parse_scene(&mut self, now: f64) {
+        if let Ok(config) = crate::dsl::parse_config(&self.dsl_code) {
+            self.fps = config.fps;
+            self.duration_secs = config.duration;
+            self.render_width = config.width;
+            self.render_height = config.height;
+        }
+
+        self.scene = crate::dsl::parse_dsl(&self.dsl_code);
+        self.scene_version += 1;
+        self.last_scene_parse_time = now;
+    }
+
+    /// The project settings as written in the DSL header.
+    pub fn header_config(&self) -> crate::dsl::ast::HeaderConfig {
+        crate::dsl::ast::HeaderConfig {
+            width: self.render_width,
+            height: self.render_height,
+            fps: self.fps,
+            duration: self.duration_secs,
+        }
+    }
+
+    /// Write the settings edited in the UI back into the `size`/`timeline`
+    /// header of `dsl_code`, so the next parse and save agree with them.
+    pub fn sync_header_to_code(&mut self, now: f64) {
+        let code = crate::dsl::generator::rewrite_header(&self.dsl_code, &self.header_config());
+        if code != self.dsl_code {
+            self.dsl_code = code;
+            self.commit_edit(EditKind::Drag, now);
+        }
+    }
+
+    /// Record the current `dsl_code` in the undo history and schedule the
+    /// autosave.  Every editor calls this after changing the code.
+    pub fn commit_edit(&mut self, kind: EditKind, now: f64) {
+        self.history.commit(&self.dsl_code, kind, now);
+        self.autosave.mark_dirty(now);
+    }
+
+    pub fn undo(&mut self, now: f64) {
+        if let Some(code) = self.history.undo() {
+            self.restore_code(code, now);
+        }
+    }
+
+    pub fn redo(&mut self, now: f64) {
+        if let Some(code) = self.history.redo() {
+            self.restore_code(code, now);
+        }
+    }
+
+    /// Put back code from the history.  A scene graph rename in progress
+    /// is closed, and the scene is parsed right away so the panels and settings show the restored state
+    /// on this frame.
+    fn restore_code(&mut self, code: String, now: f64) {
+        self.dsl_code = code;
+        self.renaming = None;
+        self.autosave.mark_dirty(now);
+        self.scene_parse_pending = None;
+        self.parse_scene(now);
+    }
+
+    /// Switch to the project in `dir`: load its manifest and entry file,
+    /// creating them from the current settings when the folder has none,
+    /// restore its canvas and timeline view, and put it on top of the
+    /// recent projects.
+    pub fn open_project(&mut self, dir: PathBuf, now: f64) -> anyhow::Result<()> {
+        let defaults = self.header_config();
+        let project = crate::project::Project::open_or_create(&dir, &defaults, |settings| {
+            crate::dsl::generator::generate_dsl_from_elements(
+                &[],
+                settings.width,
+                settings.height,
+                settings.fps,
+                settings.duration,
+            )
+        })?;
+
+        self.file_watch.watch(project.entry_path(), &project.source);
+        self.dsl_code = project.source;
+        self.history.reset(&self.dsl_code);
+        self.autosave = AutosaveState::default();
+        self.recent_projects.touch(&project.manifest.name, &dir);
+        let _ = self.recent_projects.save();
+        self.project_manifest = Some(project.manifest);
+
+        let mut views = std::mem::take(&mut self.view_store);
+        views.capture(self);
+        views.apply_project(self, &dir);
+        self.view_store = views;
+        self.project_path = Some(dir);
+        self.scene_parse_pending = None;
+        self.parse_scene(now);
+        Ok(())
+    }
+
+    /// The open project's DSL entry file.
+    pub fn entry_file(&self) -> Option<PathBuf> {
+        let dir = self.project_path.as_ref()?;
+        Some(match &self.project_manifest {
+            Some(manifest) => manifest.entry_path(dir),
+            None => dir.join(crate::project::DEFAULT_ENTRY),
+        })
+    }
+
+    pub fn timeline(&self) -> crate::timing::Timeline {
+        crate::timing::Timeline {
+            fps: self.fps,
+            duration: self.duration_secs,
+        }
+    }
+
     pub fn set_time(&mut self, seconds: f32) {
-        self.time = seconds.clamp(0.0, self.duration_secs);
+        self.time = self.timeline().clamp(seconds);
     }
 
     pub fn step_forward(&mut self) {
-        let dt = 1.0 / (self.fps as f32);
-        self.set_time(self.time + dt);
+        self.time = self.timeline().step(self.time, 1);
     }
 
     pub fn step_backward(&mut self) {
-        let dt = 1.0 / (self.fps as f32);
-        self.set_time(self.time - dt);
+        self.time = self.timeline().step(self.time, -1);
     }
 
     pub fn request_dsl_update(&mut self) {
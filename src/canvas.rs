@@ -1,8 +1,19 @@
-use crate::app_state::AppState;
 use eframe::egui;
 
+pub mod preview_worker;
 pub mod ui;
+pub use preview_worker::{poll_preview_results, request_preview_frames};
 pub use ui::show;
 
-pub fn poll_preview_results(_state: &mut AppState, _ctx: &egui::Context) {}
-pub fn request_preview_frames(_state: &mut AppState, _time: f32) {}
+/// Screen rectangle of the render frame inside the canvas `rect`.  Canvas
+/// space has its origin at the centre of `rect` shifted by the pan, with one
+/// unit per render pixel at zoom 1; the frame is centred on the origin.
+pub fn frame_rect(
+    rect: egui::Rect,
+    pan: egui::Vec2,
+    zoom: f32,
+    render_size: [u32; 2],
+) -> egui::Rect {
+    let size = egui::vec2(render_size[0] as f32, render_size[1] as f32) * zoom;
+    egui::Rect::from_center_size(rect.center() + pan, size)
+}
@@ -0,0 +1,211 @@
+//! Background preview rendering for the canvas.
+//!
+//! The UI thread never rasterizes.  [`request_preview_frames`] sends a
+//! `(scene_version, time, fps, size)` request to a worker thread, which renders it
+//! with [`crate::renderer::render_frame`] and hands the pixels back.
+//! [`poll_preview_results`] uploads the newest result into an egui texture
+//! that `canvas::ui` paints over the render frame.
+//!
+//! Requests pile up while the user scrubs or types; the worker only renders
+//! the most recent one.  Requests and results whose `scene_version` is no
+//! longer current are dropped, both before and after rendering.
+
+use crate::app_state::AppState;
+use crate::scene::Shape;
+use eframe::egui;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
+
+/// One frame the canvas wants to show.
+pub struct PreviewRequest {
+    pub scene_version: u32,
+    pub time: f32,
+    /// Frame rate, for the `frame` variable of property expressions.
+    pub fps: u32,
+    pub size: [u32; 2],
+    pub scene: Arc<Vec<Shape>>,
+}
+
+/// A rendered preview frame, straight alpha.
+pub struct PreviewResult {
+    pub scene_version: u32,
+    pub time: f32,
+    pub image: egui::ColorImage,
+}
+
+/// Handle to the worker thread.  Dropping it ends the thread.
+pub struct PreviewWorker {
+    tx: Sender<PreviewRequest>,
+    rx: Receiver<PreviewResult>,
+    /// The scene version the UI currently shows; the worker skips anything
+    /// older.
+    current_version: Arc<AtomicU32>,
+    /// Scene snapshot shared with in-flight requests, rebuilt when the
+    /// scene version changes.
+    scene: Option<(u32, Arc<Vec<Shape>>)>,
+    /// The last `(scene_version, time, fps, size)` sent, to avoid
+    /// re-requesting an unchanged frame every UI frame.
+    last_request: Option<(u32, f32, u32, [u32; 2])>,
+}
+
+impl PreviewWorker {
+    /// Start the worker thread.  `on_result` runs on the worker after each
+    /// result is sent (the UI uses it to request a repaint).
+    pub fn spawn(on_result: impl Fn() + Send + 'static) -> Self {
+        let (req_tx, req_rx) = std::sync::mpsc::channel::<PreviewRequest>();
+        let (res_tx, res_rx) = std::sync::mpsc::channel();
+        let current_version = Arc::new(AtomicU32::new(0));
+        let version = Arc::clone(&current_version);
+
+        std::thread::Builder::new()
+            .name("preview-worker".to_string())
+            .spawn(move || {
+                while let Ok(mut req) = req_rx.recv() {
+                    // only the newest request matters
+                    while let Ok(newer) = req_rx.try_recv() {
+                        req = newer;
+                    }
+                    if req.scene_version != version.load(Ordering::Acquire) {
+                        continue;
+                    }
+                    let img = crate::renderer::render_frame(
+                        &req.scene,
+                        req.time,
+                        req.fps,
+                        req.size[0],
+                        req.size[1],
+                    );
+                    // the scene may have changed while we were rendering
+                    if req.scene_version != version.load(Ordering::Acquire) {
+                        continue;
+                    }
+                    let image = egui::ColorImage::from_rgba_unmultiplied(
+                        [img.width() as usize, img.height() as usize],
+                        img.as_raw(),
+                    );
+                    let result = PreviewResult {
+                        scene_version: req.scene_version,
+                        time: req.time,
+                        image,
+                    };
+                    if res_tx.send(result).is_err() {
+                        break;
+                    }
+                    on_result();
+                }
+            })
+            .expect("failed to spawn preview worker thread");
+
+        Self {
+            tx: req_tx,
+            rx: res_rx,
+            current_version,
+            scene: None,
+            last_request: None,
+        }
+    }
+
+    /// Queue a render of `scene` unless the same frame was already requested.
+    pub fn request(
+        &mut self,
+        scene: &[Shape],
+        scene_version: u32,
+        time: f32,
+        fps: u32,
+        size: [u32; 2],
+    ) {
+        self.current_version.store(scene_version, Ordering::Release);
+        if self.last_request == Some((scene_version, time, fps, size)) {
+            return;
+        }
+        let snapshot = match &self.scene {
+            Some((v, s)) if *v == scene_version => Arc::clone(s),
+            _ => {
+                let s = Arc::new(scene.to_vec());
+                self.scene = Some((scene_version, Arc::clone(&s)));
+                s
+            }
+        };
+        self.last_request = Some((scene_version, time, fps, size));
+        let _ = self.tx.send(PreviewRequest {
+            scene_version,
+            time,
+            fps,
+            size,
+            scene: snapshot,
+        });
+    }
+
+    /// The newest finished frame for `scene_version`, if any arrived.
+    pub fn latest(&self, scene_version: u32) -> Option<PreviewResult> {
+        self.rx
+            .try_iter()
+            .filter(|r| r.scene_version == scene_version)
+            .last()
+    }
+}
+
+/// Ask for the frame at `time` at `size` pixels (clamped to at least 1x1).
+/// Does nothing until [`poll_preview_results`] has started the worker.
+pub fn request_preview_frames(state: &mut AppState, time: f32, size: [u32; 2]) {
+    let size = [size[0].max(1), size[1].max(1)];
+    let (version, fps) = (state.scene_version, state.fps);
+    if let Some(worker) = state.preview_worker.as_mut() {
+        worker.request(&state.scene, version, time, fps, size);
+    }
+}
+
+/// Start the worker if needed and upload the newest current result.
+pub fn poll_preview_results(state: &mut AppState, ctx: &egui::Context) {
+    let worker = state.preview_worker.get_or_insert_with(|| {
+        let ctx = ctx.clone();
+        PreviewWorker::spawn(move || ctx.request_repaint())
+    });
+    let Some(result) = worker.latest(state.scene_version) else {
+        return;
+    };
+    match &mut state.preview_texture {
+        Some(texture) => texture.set(result.image, egui::TextureOptions::LINEAR),
+        None => {
+            state.preview_texture =
+                Some(ctx.load_texture("canvas_preview", result.image, egui::TextureOptions::LINEAR))
+        }
+    }
+    state.preview_time = Some(result.time);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn wait_for(worker: &PreviewWorker, version: u32) -> Option<PreviewResult> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if let Some(r) = worker.latest(version) {
+                return Some(r);
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        None
+    }
+
+    #[test]
+    fn renders_requests_off_thread_and_drops_stale_versions() {
+        let scene = crate::dsl::parse_dsl("rect \"A\" { x = 0.5, y = 0.5, w = 1, h = 1 }");
+        let mut worker = PreviewWorker::spawn(|| {});
+
+        worker.request(&scene, 1, 0.0, 60, [8, 4]);
+        let first = wait_for(&worker, 1).expect("a result for version 1");
+        assert_eq!(first.image.size, [8, 4]);
+        assert_eq!(first.image.pixels[0], egui::Color32::WHITE);
+
+        // the scene changes again before version 2 is (likely) rendered:
+        // only the version 3 frame may reach the UI
+        worker.request(&scene, 2, 0.5, 60, [2, 2]);
+        worker.request(&scene, 3, 0.5, 60, [3, 3]);
+        let third = wait_for(&worker, 3).expect("a result for version 3");
+        assert_eq!((third.scene_version, third.image.size), (3, [3, 3]));
+    }
+}
@@ -19,6 +19,37 @@ pub fn show(ui: &mut egui::Ui, state: &mut AppState, _main_ui_enabled: bool) {
         egui::vec2(state.canvas_pan_x, state.canvas_pan_y),
     );
 
+    // Render frame with the latest preview on top
+    let frame = crate::canvas::frame_rect(
+        rect,
+        egui::vec2(state.canvas_pan_x, state.canvas_pan_y),
+        state.canvas_zoom,
+        [state.render_width, state.render_height],
+    );
+    painter.rect_filled(frame, 0.0, egui::Color32::from_rgb(28, 28, 30));
+    if let Some(texture) = &state.preview_texture {
+        painter.image(
+            texture.id(),
+            frame,
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+    }
+    painter.rect_stroke(
+        frame,
+        0.0,
+        egui::Stroke::new(1.0, egui::Color32::from_white_alpha(60)),
+    );
+
+    // Preview resolution follows the on-screen size, capped at the render size
+    let ppp = ui.ctx().pixels_per_point();
+    let preview_size = [
+        ((frame.width() * ppp).round() as u32).min(state.render_width),
+        ((frame.height() * ppp).round() as u32).min(state.render_height),
+    ];
+    crate::canvas::poll_preview_results(state, ui.ctx());
+    crate::canvas::request_preview_frames(state, state.time, preview_size);
+
     // UI Overlay (Transport Controls)
     transport_bar::show(ui, state, rect);
 
@@ -16,7 +16,7 @@ pub fn show(ui: &mut egui::Ui, state: &mut AppState) {
             state.show_settings = !state.show_settings;
         }
         ui.add_space(4.0);
-        
+
         let is_scene = state.active_tab == Some(PanelTab::SceneGraph);
         if toolbar_button(ui, "☰", "Scene Graph", button_size, is_scene)
             .on_hover_text("Scene Graph")
@@ -42,6 +42,15 @@ pub fn show(ui: &mut egui::Ui, state: &mut AppState) {
                 state.active_tab = Some(PanelTab::Code);
             }
         }
+
+        ui.add_space(4.0);
+
+        if toolbar_button(ui, "⬇", "Export", button_size, state.show_export)
+            .on_hover_text("Export")
+            .clicked()
+        {
+            state.show_export = !state.show_export;
+        }
     });
 }
 
@@ -1,22 +1,17 @@
+use crate::dsl::lexer::{self, TokenKind};
 use eframe::egui;
 
 pub(crate) fn highlight_code<F>(
     job: &mut egui::text::LayoutJob,
     code: &str,
+    font_id: &egui::FontId,
     // predicate which returns `true` if the given identifier should be
     // highlighted as a defined object name. taking a predicate allows the
     // caller to avoid allocating or cloning a collection for every frame.
     is_defined: F,
-    handlers: &[crate::dsl::runtime::DslHandler],
 ) where
     F: Fn(&str) -> bool,
 {
-    let font_id = egui::FontId::monospace(14.0);
-
-    // Simple tokenizer based on characters
-    let mut chars = code.char_indices().peekable();
-    let mut last_idx = 0;
-
     // Rainbow bracket colors (Pastel/Neon for dark theme)
     let rainbow_colors = [
         egui::Color32::from_rgb(255, 100, 100), // Red
@@ -28,310 +23,93 @@ pub(crate) fn highlight_code<F>(
         egui::Color32::from_rgb(255, 100, 200), // Pink
     ];
     let mut bracket_depth: usize = 0;
+    let mut last_idx = 0;
 
-    while let Some((idx, c)) = chars.next() {
-        // If there are diagnostics for this region, we could add inline underline
-        // but keep highlighting logic fast — gutter will show markers and tooltips.
-        // 1. Whitespace
-        if c.is_whitespace() {
-            if idx > last_idx {
-                append_text(
-                    job,
-                    &code[last_idx..idx],
-                    &font_id,
-                    egui::Color32::LIGHT_GRAY,
-                );
-            }
-            append_text(
-                job,
-                &code[idx..idx + 1],
-                &font_id,
-                egui::Color32::LIGHT_GRAY,
-            );
-            last_idx = idx + 1;
-            continue;
-        }
-
-        // 2. Comments (// ...)
-        if c == '/' {
-            if let Some((_, '/')) = chars.peek() {
-                chars.next(); // eat second slash
-                let start = idx;
-                let mut end = idx + 2;
-                while let Some((i, next_c)) = chars.peek() {
-                    if *next_c == '\n' {
-                        break;
-                    }
-                    end = *i + 1;
-                    chars.next();
-                }
-                append_text(
-                    job,
-                    &code[last_idx..start],
-                    &font_id,
-                    egui::Color32::LIGHT_GRAY,
-                );
-                append_text(
-                    job,
-                    &code[start..end],
-                    &font_id,
-                    egui::Color32::from_rgb(90, 120, 90),
-                ); // Greenish comment
-                last_idx = end;
-                continue;
-            }
-        }
-
-        // 3. Strings ("...")
-        if c == '"' {
-            append_text(
-                job,
-                &code[last_idx..idx],
-                &font_id,
-                egui::Color32::LIGHT_GRAY,
-            );
-
-            let start = idx;
-            let mut end = idx + 1;
-            for (i, next_c) in chars.by_ref() {
-                end = i + 1;
-                if next_c == '"' {
-                    break;
-                }
-            }
-
-            let content = &code[start..end];
-            // Check if it's a hex color: "#RRGGBB" or "#RRGGBBAA"
-            if content.len() == 9 || content.len() == 11 {
-                let inner = &content[1..content.len() - 1];
-                if let Some(c) = crate::code_panel::utils::parse_hex(inner) {
+    for token in lexer::tokenize(code) {
+        // Whitespace between tokens keeps the default color
+        append_text(
+            job,
+            &code[last_idx..token.span.start],
+            font_id,
+            egui::Color32::LIGHT_GRAY,
+        );
+        last_idx = token.span.end;
+        let text = token.text(code);
+
+        let color = match token.kind {
+            TokenKind::Comment => egui::Color32::from_rgb(90, 120, 90), // Greenish comment
+            TokenKind::Str { terminated } => {
+                // Check if it's a hex color: "#RRGGBB" or "#RRGGBBAA"
+                let inner = lexer::unescape(text);
+                let hex = crate::dsl::utils::parse_hex_color(&inner).filter(|_| terminated);
+                if let Some(c) = hex {
                     // Highlight hex content with its actual color
-                    append_text(job, "\"", &font_id, egui::Color32::from_rgb(206, 145, 120));
-                    append_text(
-                        job,
-                        inner,
-                        &font_id,
-                        egui::Color32::from_rgba_unmultiplied(c[0], c[1], c[2], c[3]),
-                    );
-                    append_text(job, "\"", &font_id, egui::Color32::from_rgb(206, 145, 120));
-                } else {
-                    append_text(
-                        job,
-                        content,
-                        &font_id,
-                        egui::Color32::from_rgb(206, 145, 120),
-                    );
+                    let quote = egui::Color32::from_rgb(206, 145, 120);
+                    append_text(job, "\"", font_id, quote);
+                    append_text(job, &text[1..text.len() - 1], font_id, hex_color(c));
+                    append_text(job, "\"", font_id, quote);
+                    continue;
                 }
-            } else {
-                append_text(
-                    job,
-                    content,
-                    &font_id,
-                    egui::Color32::from_rgb(206, 145, 120),
-                );
+                egui::Color32::from_rgb(206, 145, 120)
             }
-            last_idx = end;
-            continue;
-        }
-
-        // 4. Brackets (Rainbow)
-        if "()[]{}".contains(c) {
-            append_text(
-                job,
-                &code[last_idx..idx],
-                &font_id,
-                egui::Color32::LIGHT_GRAY,
-            );
-
-            let color_idx = if ")]}".contains(c) {
-                if bracket_depth > 0 {
-                    bracket_depth = bracket_depth.saturating_sub(1);
-                }
-                bracket_depth
-            } else {
+            TokenKind::HexColor => crate::dsl::utils::parse_hex_color(text)
+                .map(hex_color)
+                .unwrap_or(egui::Color32::LIGHT_GRAY),
+            TokenKind::Open(_) => {
                 let d = bracket_depth;
                 bracket_depth += 1;
-                d
-            };
-
-            let color = rainbow_colors[color_idx % rainbow_colors.len()];
-            append_text(job, &code[idx..idx + 1], &font_id, color);
-            last_idx = idx + 1;
-            continue;
-        }
-
-        // 5. DSL Blocks, Parameters and Identifiers
-        if c.is_alphabetic() || c == '_' {
-            if idx > last_idx {
-                append_text(
-                    job,
-                    &code[last_idx..idx],
-                    &font_id,
-                    egui::Color32::LIGHT_GRAY,
-                );
+                rainbow_colors[d % rainbow_colors.len()]
             }
-
-            let start = idx;
-            let mut end = idx + 1;
-            while let Some((i, next_c)) = chars.peek() {
-                if next_c.is_alphanumeric() || *next_c == '_' {
-                    end = *i + 1;
-                    chars.next();
-                } else {
-                    break;
-                }
-            }
-
-            let word = &code[start..end];
-            // If this identifier matches a registered DSL handler, use its color.
-            if let Some(h) = handlers.iter().find(|h| h.name == word) {
-                let c = h.color;
-                append_text(
-                    job,
-                    word,
-                    &font_id,
-                    egui::Color32::from_rgba_unmultiplied(c[0], c[1], c[2], c[3]),
-                );
-                last_idx = end;
-                continue;
-            }
-
-            // If this identifier is a move-element-style utility, try to infer a color
-            // from an upcoming `color = "#rrggbb"` parameter in the call. Fallback to
-            // the default object-teal color.
-            // If this identifier is a known DSL *method* (e.g. `move_element`),
-            // try to parse the call to infer a per-call color; otherwise use
-            // the centralized method color registry as a fallback. This makes it
-            // trivial to add new methods + colors via `dsl::method_color()`.
-            if let Some(mcol) = crate::dsl::method_color(word) {
-                let rest = &code[end..];
-                if let Some(open_paren_pos) = rest.find('(') {
-                    if let Some(close_paren_pos) =
-                        crate::code_panel::utils::find_matching_paren(rest, open_paren_pos)
-                    {
-                        if let Some(call_end) = end.checked_add(close_paren_pos + 1) {
-                            if call_end <= code.len() {
-                                let call_substr = &code[start..call_end];
-                                // Special-case parsing for move_element (centralized parser)
-                                if word == "move_element" {
-                                    if let Ok(me) = crate::shapes::utilities::move_element::MoveElement::parse_dsl(call_substr) {
-                                        let c = me.color;
-                                        append_text(job, word, &font_id, egui::Color32::from_rgba_unmultiplied(c[0], c[1], c[2], c[3]));
-                                        last_idx = end;
-                                        continue;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Fallback to the registry color for this method
-                append_text(
-                    job,
-                    word,
-                    &font_id,
-                    egui::Color32::from_rgba_unmultiplied(mcol[0], mcol[1], mcol[2], mcol[3]),
-                );
-                last_idx = end;
-                continue;
+            TokenKind::Close(_) => {
+                bracket_depth = bracket_depth.saturating_sub(1);
+                rainbow_colors[bracket_depth % rainbow_colors.len()]
             }
+            TokenKind::Number { .. } => egui::Color32::from_rgb(181, 206, 168), // Light Green number
+            TokenKind::Op(_) | TokenKind::Comma => egui::Color32::from_rgb(212, 212, 212), // Subtle gray for ops
+            TokenKind::Unknown => egui::Color32::from_rgb(244, 71, 71),
+            TokenKind::Ident => word_color(text, &is_defined),
+        };
 
-            // --- shape keywords ------------------------------------------------
-            // instead of hard-coding "circle"/"rect" here, consult the shape
-            // manager registry which already knows about every shape type.  each
-            // `ShapeDescriptor` can specify its own DSL colour via `dsl_color()`.
-            if let Some(col) = crate::shapes::shapes_manager::keyword_color(word) {
-                append_text(job, word, &font_id, col);
-                last_idx = end;
-                continue;
-            }
-
-            let color = match word {
-                // Primary Block / control keywords (non‑shape)
-                "move" | "size" | "timeline" | "let" | "set" | "for" | "if" | "not" | "in" => {
-                    egui::Color32::from_rgb(86, 156, 214)
-                } // Blue (#569CD6)
-
-                // Parameters / Properties
-                "name" | "x" | "y" | "radius" | "width" | "height" | "fill"
-                | "spawn" | "to" | "during" | "ease" | "startAt" | "time" | "element" | "type"
-                | "fps" | "duration" => egui::Color32::from_rgb(156, 220, 254), // Light Blue (#9CDCFE)
-
-                // Values / Constants / Easings
-                "linear" | "ease_in" | "ease_out" | "ease_in_out" | "bezier" | "custom"
-                | "sine" | "expo" | "circ" | "spring" | "elastic" | "bounce" | "points"
-                | "power" | "damping" | "stiffness" | "mass" | "amplitude" | "period"
-                | "bounciness" => egui::Color32::from_rgb(220, 220, 170), // Gold (#DCDCAA)
-
-                _ => {
-                    // Check if this is a defined object name
-                    if is_defined(word) {
-                        egui::Color32::from_rgb(78, 201, 176) // Teal (#4EC9B0) for Objects
-                    } else {
-                        egui::Color32::LIGHT_GRAY
-                    }
-                }
-            };
+        append_text(job, text, font_id, color);
+    }
 
-            append_text(job, word, &font_id, color);
-            last_idx = end;
-            continue;
-        }
+    // Flush remaining
+    append_text(job, &code[last_idx..], font_id, egui::Color32::LIGHT_GRAY);
+}
 
-        // 6. Numbers
-        if c.is_ascii_digit() {
-            if idx > last_idx {
-                append_text(
-                    job,
-                    &code[last_idx..idx],
-                    &font_id,
-                    egui::Color32::LIGHT_GRAY,
-                );
-            }
-            let start = idx;
-            let mut end = idx + 1;
-            while let Some((i, next_c)) = chars.peek() {
-                if next_c.is_ascii_digit() || *next_c == '.' {
-                    end = *i + 1;
-                    chars.next();
-                } else {
-                    break;
-                }
+fn word_color(word: &str, is_defined: &impl Fn(&str) -> bool) -> egui::Color32 {
+    match word {
+        // Shape keywords
+        "rect" => egui::Color32::from_rgb(78, 201, 176),
+
+        // Primary Block / control keywords (non‑shape)
+        "move" | "animations" | "size" | "timeline" | "let" | "set" | "for" | "if" | "not"
+        | "in" => egui::Color32::from_rgb(86, 156, 214), // Blue (#569CD6)
+
+        // Parameters / Properties
+        "name" | "x" | "y" | "w" | "h" | "color" | "radius" | "width" | "height" | "fill"
+        | "spawn" | "to" | "during" | "ease" | "startAt" | "time" | "element" | "type" | "fps"
+        | "duration" => egui::Color32::from_rgb(156, 220, 254), // Light Blue (#9CDCFE)
+
+        // Values / Constants / Easings
+        "linear" | "step" | "steps" | "cubic_bezier" | "back" | "elastic" | "bounce" | "spring"
+        | "jump_start" | "jump_end" | "power" | "overshoot" | "amplitude" | "period"
+        | "stiffness" | "damping" | "mass" => egui::Color32::from_rgb(220, 220, 170),
+        w if w.starts_with("ease_") => egui::Color32::from_rgb(220, 220, 170), // Gold (#DCDCAA)
+
+        _ => {
+            // Check if this is a defined object name
+            if is_defined(word) {
+                egui::Color32::from_rgb(78, 201, 176) // Teal (#4EC9B0) for Objects
+            } else {
+                egui::Color32::LIGHT_GRAY
             }
-            append_text(
-                job,
-                &code[start..end],
-                &font_id,
-                egui::Color32::from_rgb(181, 206, 168),
-            ); // Light Green number
-            last_idx = end;
-            continue;
-        }
-
-        // 7. Operators and separators
-        if "=->,".contains(c) {
-            append_text(
-                job,
-                &code[last_idx..idx],
-                &font_id,
-                egui::Color32::LIGHT_GRAY,
-            );
-            append_text(
-                job,
-                &code[idx..idx + 1],
-                &font_id,
-                egui::Color32::from_rgb(212, 212, 212),
-            ); // Subtle gray for ops
-            last_idx = idx + 1;
-            continue;
         }
     }
+}
 
-    // Flush remaining
-    if last_idx < code.len() {
-        append_text(job, &code[last_idx..], &font_id, egui::Color32::LIGHT_GRAY);
-    }
+fn hex_color(c: [u8; 4]) -> egui::Color32 {
+    egui::Color32::from_rgba_unmultiplied(c[0], c[1], c[2], c[3])
 }
 
 fn append_text(
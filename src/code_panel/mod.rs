@@ -1,5 +1,47 @@
 use crate::app_state::AppState;
+use crate::states::history::EditKind;
 use eframe::egui;
+use std::collections::HashSet;
+
+pub mod autocomplete;
+mod highlighter;
+pub mod utils;
+
+/// Save state shown on the right of the header bar.
+fn autosave_status(ui: &mut egui::Ui, state: &AppState) {
+    if state.project_path.is_none() {
+        return;
+    }
+    let autosave = &state.autosave;
+    if let Some(error) = &autosave.error {
+        ui.label(
+            egui::RichText::new("⚠ Not saved")
+                .color(egui::Color32::from_rgb(255, 120, 120))
+                .size(12.0),
+        )
+        .on_hover_text(error);
+    } else if autosave.pending {
+        ui.label(egui::RichText::new("Unsaved").weak().size(12.0));
+    } else if autosave.last_success_time.is_some() {
+        ui.label(egui::RichText::new("Saved").weak().size(12.0));
+    }
+
+    let warnings: Vec<String> = state
+        .dsl
+        .diagnostics
+        .iter()
+        .filter(|d| !d.is_error())
+        .map(|d| format!("{}:{}: {}", d.line, d.column, d.message))
+        .collect();
+    if !warnings.is_empty() {
+        ui.label(
+            egui::RichText::new(format!("⚠ {}", warnings.len()))
+                .color(egui::Color32::from_rgb(230, 190, 90))
+                .size(12.0),
+        )
+        .on_hover_text(warnings.join("\n"));
+    }
+}
 
 pub fn show(ui: &mut egui::Ui, state: &mut AppState) {
     let top_bar_bg = egui::Color32::from_rgb(37, 37, 38);
@@ -18,6 +60,9 @@ pub fn show(ui: &mut egui::Ui, state: &mut AppState) {
                         .color(egui::Color32::from_rgb(224, 224, 224))
                         .size(13.0),
                 );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    autosave_status(ui, state);
+                });
             });
         });
 
@@ -30,15 +75,15 @@ pub fn show(ui: &mut egui::Ui, state: &mut AppState) {
     );
     ui.add_space(1.0);
 
-    let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx());
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+
+    let defined: HashSet<String> = state.scene.iter().map(|s| s.name().to_string()).collect();
     let mut layouter = |ui: &egui::Ui, string: &str, _wrap_width: f32| {
-        let mut layout_job =
-            egui_extras::syntax_highlighting::highlight(ui.ctx(), &theme, string, "rs");
+        let mut layout_job = egui::text::LayoutJob::default();
+        highlighter::highlight_code(&mut layout_job, string, &font_id, |w| defined.contains(w));
         layout_job.wrap.max_width = f32::INFINITY; // Disable wrap to keep line numbers perfectly synced
         ui.fonts(|f| f.layout_job(layout_job))
     };
-
-    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
     let row_height = ui.fonts(|f| f.row_height(&font_id));
 
     let available_height = ui.available_height();
@@ -108,18 +153,20 @@ pub fn show(ui: &mut egui::Ui, state: &mut AppState) {
                         .margin(egui::vec2(0.0, 0.0))
                         .lock_focus(true)
                         .layouter(&mut layouter);
-                    
+
                     text_output = Some(output.show(ui));
                 });
 
                 if let Some(text_out) = text_output {
                     // If user clicks in the empty area below the text, focus the text editor
-                    if ui.rect_contains_pointer(text_rect) && ui.input(|i| i.pointer.primary_clicked()) {
+                    if ui.rect_contains_pointer(text_rect)
+                        && ui.input(|i| i.pointer.primary_clicked())
+                    {
                         text_out.response.request_focus();
                     }
 
                     if text_out.response.changed() {
-                        state.autosave.mark_dirty(ui.input(|i| i.time));
+                        state.commit_edit(EditKind::Typing, ui.input(|i| i.time));
                     }
                 }
             });
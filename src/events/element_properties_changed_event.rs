@@ -1,66 +1,64 @@
-use std::fs;
+use crate::app_state::AppState;
+use crate::states::autosave;
+use crate::states::history::EditKind;
 
-use crate::app_state::{AppState, ToastType};
-
-/// Low-level writer for `code.motioner`.
-/// When `show_toast` is false the operation is silent (used for autosave).
-pub fn write_dsl_to_project(state: &mut AppState, show_toast: bool) -> std::io::Result<()> {
-    let project_dir = match state.project_path.as_ref() {
-        Some(p) => p,
-        None => return Ok(()),
+/// Low-level writer for the project's entry file (`code.motioner`).
+///
+/// The code is validated and its indentation normalized to tabs first.
+/// Invalid code is not written: its diagnostics are stored on `state.dsl`
+/// and the first error becomes the error.  Warnings do not block the write
+/// and are kept on `state.dsl`.  When the header settings changed,
+/// the manifest's copy is updated too.  Does nothing when no project is
+/// open.
+pub fn write_dsl_to_project(state: &mut AppState) -> std::io::Result<()> {
+    let Some(dst) = state.entry_file() else {
+        return Ok(());
     };
 
-    // Validate DSL before writing — if there are diagnostics, do not save.
-    // We also normalize the source text to use tabs while we're at it.
     let diags = crate::dsl::utils::validate_and_normalize(&mut state.dsl_code);
-    if !diags.is_empty() {
-        state.dsl.diagnostics = diags.clone();
-        if show_toast {
-            state.toast_message = Some("Save failed: DSL errors".to_string());
-            state.toast_type = crate::app_state::ToastType::Error;
-            // We don't have access to egui::Context here; approximate deadline
-            state.toast_deadline = state.last_update as f64 + 3.0;
-        }
-        return Err(std::io::Error::other(diags[0].message.clone()));
+    let first_error = diags
+        .iter()
+        .find(|d| d.is_error())
+        .map(|d| d.message.clone());
+    state.dsl.diagnostics = diags;
+    if let Some(message) = first_error {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            message,
+        ));
     }
 
-    let dst = project_dir.join("code.motioner");
-    fs::write(&dst, &state.dsl_code)?;
-    state.last_export_path = Some(dst.clone());
-    // After successful save, we still need to make sure the on-disk version
-    // uses tabs. The earlier helper already normalized `state.dsl_code` but
-    // we must re-validate the normalized string before overwriting the file
-    // (this mirrors the previous behaviour).
-    let normalized = crate::dsl::generator::normalize_tabs(&state.dsl_code);
-    if normalized != state.dsl_code {
-        let diags = crate::dsl::validate_dsl(&normalized);
-        if diags.is_empty() {
-            fs::write(&dst, &normalized)?;
-            state.dsl_code = normalized;
+    autosave::rotate_backups(&dst, autosave::BACKUP_COUNT, autosave::BACKUP_INTERVAL)?;
+    crate::project::write_atomic(&dst, state.dsl_code.as_bytes())?;
+    state.file_watch.record_write(&state.dsl_code);
+
+    if let (Some(dir), Some(manifest), Ok(header)) = (
+        state.project_path.as_ref(),
+        state.project_manifest.as_mut(),
+        crate::dsl::parse_config(&state.dsl_code),
+    ) {
+        if manifest.settings != header {
+            manifest.settings = header;
+            manifest.save(dir).map_err(std::io::Error::other)?;
         }
     }
-    // Clear diagnostics on successful save
-    state.dsl.diagnostics.clear();
-    if show_toast {
-        state.toast_message = Some(format!(
-            "Saved {}",
-            dst.file_name().unwrap().to_string_lossy()
-        ));
-        state.toast_type = ToastType::Success;
-    }
     Ok(())
 }
 
-/// Public event: call this when element properties change and you want the
-/// user to be notified on success/failure. This delegates to the writer
-/// with toast enabled.
-pub fn on_element_properties_changed(state: &mut AppState) {
-    let _ = write_dsl_to_project(state, true);
+/// Public event: call this when a structural edit (scene graph, canvas)
+/// rewrote `state.dsl_code`.  The edit becomes one undo step and the code
+/// is saved immediately rather than after the autosave cooldown.
+pub fn on_element_properties_changed(state: &mut AppState, now: f64) {
+    state
+        .history
+        .commit(&state.dsl_code, EditKind::Structural, now);
+    autosave::save_now(state, now);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use tempfile::tempdir;
 
     #[test]
@@ -72,30 +70,39 @@ mod tests {
         // invalid DSL: missing header and top-level move without element
         state.dsl_code = "move { to = (0.1, 0.2) }".to_string();
 
-        let res = write_dsl_to_project(&mut state, false);
-        // With the parser/validator currently disabled the string is
-        // accepted; the write should succeed and no diagnostics are
-        // produced.  Once we re-enable DSL checking this test will need
-        // to be revised again.
-        assert!(res.is_ok());
-        assert!(state.dsl.diagnostics.is_empty());
+        let res = write_dsl_to_project(&mut state);
+        assert!(res.is_err());
+        assert!(!state.dsl.diagnostics.is_empty());
 
         // Ensure file was not written
         let dst = td.path().join("code.motioner");
         assert!(!dst.exists());
     }
 
+    #[test]
+    fn write_dsl_keeps_warnings_without_blocking() {
+        let mut state = crate::app_state::AppState::default();
+        let td = tempdir().expect("tempdir");
+        state.project_path = Some(td.path().to_path_buf());
+        state.dsl_code =
+            "size(1280, 720)\ntimeline(fps = 60, duration = 5)\nlet spare = 1\n".to_string();
+
+        write_dsl_to_project(&mut state).expect("warnings do not block the write");
+        assert!(td.path().join("code.motioner").exists());
+        assert_eq!(state.dsl.diagnostics.len(), 1);
+        assert!(!state.dsl.diagnostics[0].is_error());
+    }
+
     #[test]
     fn write_dsl_converts_leading_spaces_to_tabs_on_success() {
         let mut state = crate::app_state::AppState::default();
         let td = tempdir().expect("tempdir");
         state.project_path = Some(td.path().to_path_buf());
 
-        // valid DSL (includes header) but indented with spaces; use a text
-        // element since rectangles have been removed.
-        state.dsl_code = "size(1280, 720)\ntimeline(fps = 60, duration = 5.00)\n\ntext \"T\" {\n    x = 0.5,\n    y = 0.5,\n    value = \"Hi\",\n    size = 24.0,\n    spawn = 0.0\n}\n".to_string();
+        // valid DSL (includes header) but indented with spaces
+        state.dsl_code = "size(1280, 720)\ntimeline(fps = 60, duration = 5.00)\n\nrect \"R\" {\n    x = 0.5,\n    y = 0.5,\n    w = 0.2,\n    h = 0.2\n}\n".to_string();
 
-        let res = write_dsl_to_project(&mut state, false);
+        let res = write_dsl_to_project(&mut state);
         assert!(res.is_ok());
 
         let dst = td.path().join("code.motioner");
@@ -1,101 +1,169 @@
-use crate::dsl::evaluator::{self, EvalContext, Value};
+use crate::dsl::evaluator::{self, CompiledExpr, EvalContext};
 use crate::scene::Shape;
 
-fn eval_condition(cond: &str, ctx: &EvalContext) -> Result<bool, String> {
-    let s = cond.trim();
-    // comparison operators (check two-char operators first)
-    if let Some(idx) = s.find("<=") {
-        let (l, r) = s.split_at(idx);
-        let rv = evaluator::evaluate(r.trim_start_matches("<=").trim(), ctx)?;
-        let lv = evaluator::evaluate(l.trim(), ctx)?;
-        return Ok(lv <= rv);
+/// One `if` / `else if` / `else` arm: its condition (`None` for `else`)
+/// with the condition's offset in the block, and the text between its
+/// braces.
+struct Branch<'a> {
+    cond: Option<(CompiledExpr, usize)>,
+    body: &'a str,
+}
+
+/// Index of the `}` closing the `{` at `open`.
+fn matching_brace(src: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_str = false;
+    for (i, c) in src[open..].char_indices() {
+        match c {
+            '"' => in_str = !in_str,
+            '{' if !in_str => depth += 1,
+            '}' if !in_str => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
     }
-    if let Some(idx) = s.find(">=") {
-        let (l, r) = s.split_at(idx);
-        let rv = evaluator::evaluate(r.trim_start_matches(">=").trim(), ctx)?;
-        let lv = evaluator::evaluate(l.trim(), ctx)?;
-        return Ok(lv >= rv);
+    None
+}
+
+/// `src` without the keyword `kw`, if it starts with it as a whole word.
+fn strip_keyword<'a>(src: &'a str, kw: &str) -> Option<&'a str> {
+    let rest = src.strip_prefix(kw)?;
+    match rest.chars().next() {
+        Some(c) if c.is_alphanumeric() || c == '_' => None,
+        _ => Some(rest),
     }
-    if let Some(idx) = s.find("==") {
-        let (l, r) = s.split_at(idx);
-        let lhs = l.trim();
-        let rhs = r.trim_start_matches("==").trim();
-        // try numeric compare first
-        if let (Ok(ln), Ok(rn)) = (evaluator::evaluate(lhs, ctx), evaluator::evaluate(rhs, ctx)) {
-            return Ok((ln - rn).abs() < 1e-6);
-        }
-        // fallback to string compare
-        if let (Some(ls), Some(rs)) = (ctx.get_str(lhs), ctx.get_str(rhs)) {
-            return Ok(ls == rs);
+}
+
+/// Split an `if` chain into its branches, compiling every condition so
+/// syntax errors are reported even in branches that are not taken.
+fn parse_chain(block: &str) -> Result<Vec<Branch<'_>>, String> {
+    let mut branches = Vec::new();
+    let mut rest = strip_keyword(block.trim_start(), "if").ok_or("if: invalid header")?;
+    let mut has_cond = true;
+
+    loop {
+        let brace = rest.find('{').ok_or("if: missing '{' in block")?;
+        let end_brace = matching_brace(rest, brace).ok_or("if: missing '}' in block")?;
+        let cond = if has_cond {
+            let text = &rest[..brace];
+            if text.trim().is_empty() {
+                return Err("if: missing condition".to_string());
+            }
+            // `rest` is always a suffix of `block`
+            let offset = block.len() - rest.len();
+            let expr = evaluator::compile(text).map_err(|e| format!("if: {}", e.offset(offset)))?;
+            Some((expr, offset))
+        } else if rest[..brace].trim().is_empty() {
+            None
+        } else {
+            return Err("if: expected '{' after 'else'".to_string());
+        };
+        branches.push(Branch {
+            cond,
+            body: &rest[brace + 1..end_brace],
+        });
+
+        let after = rest[end_brace + 1..].trim_start();
+        if after.is_empty() {
+            return Ok(branches);
         }
-        return Ok(false);
-    }
-    if let Some(idx) = s.find("!=") {
-        let (l, r) = s.split_at(idx);
-        let lhs = l.trim();
-        let rhs = r.trim_start_matches("!=").trim();
-        if let (Ok(ln), Ok(rn)) = (evaluator::evaluate(lhs, ctx), evaluator::evaluate(rhs, ctx)) {
-            return Ok((ln - rn).abs() >= 1e-6);
+        if !has_cond {
+            return Err("if: unexpected text after 'else' block".to_string());
         }
-        if let (Some(ls), Some(rs)) = (ctx.get_str(lhs), ctx.get_str(rhs)) {
-            return Ok(ls != rs);
+        let after_else = strip_keyword(after, "else")
+            .ok_or("if: expected 'else' or end of block")?
+            .trim_start();
+        match strip_keyword(after_else, "if") {
+            Some(cond) => rest = cond,
+            None => {
+                rest = after_else;
+                has_cond = false;
+            }
         }
-        return Ok(true);
-    }
-    if let Some(idx) = s.find('<') {
-        let (l, r) = s.split_at(idx);
-        let rv = evaluator::evaluate(r.trim_start_matches('<').trim(), ctx)?;
-        let lv = evaluator::evaluate(l.trim(), ctx)?;
-        return Ok(lv < rv);
-    }
-    if let Some(idx) = s.find('>') {
-        let (l, r) = s.split_at(idx);
-        let rv = evaluator::evaluate(r.trim_start_matches('>').trim(), ctx)?;
-        let lv = evaluator::evaluate(l.trim(), ctx)?;
-        return Ok(lv > rv);
     }
+}
 
-    // plain identifier or expression: numeric truthiness, string/list emptiness
-    if let Some(v) = ctx.variables.get(s) {
-        match v {
-            Value::Number(n) => return Ok(*n != 0.0),
-            Value::Str(st) => return Ok(!st.is_empty()),
-            Value::List(l) => return Ok(!l.is_empty()),
+/// Body of the first branch whose condition holds, or of the `else` branch;
+/// `None` when no branch applies.  Conditions must evaluate to a boolean.
+pub fn select_branch<'a>(block: &'a str, ctx: &EvalContext) -> Result<Option<&'a str>, String> {
+    for branch in parse_chain(block)? {
+        let taken = match &branch.cond {
+            Some((cond, offset)) => cond
+                .eval_bool(ctx)
+                .map_err(|e| format!("if: {}", e.offset(*offset)))?,
+            None => true,
+        };
+        if taken {
+            return Ok(Some(branch.body));
         }
     }
-
-    // try numeric expression
-    if let Ok(n) = evaluator::evaluate(s, ctx) {
-        return Ok(n != 0.0);
-    }
-
-    Ok(false)
+    Ok(None)
 }
 
-/// Execute `if` / `if not` blocks. Syntax:
+/// Execute `if` blocks. Syntax:
 /// - `if <cond> { ... }`
-/// - `if not <cond> { ... }`
+/// - `if <cond> { ... } else if <cond> { ... } else { ... }`
+///
+/// Conditions are evaluator expressions, e.g. `frame > 10 and not done`.
+/// The selected body goes to `runtime::exec_block`, which is still a stub,
+/// so nothing calls this yet; only branch selection is in use.
 pub fn exec(shapes: &mut [Shape], block: &str, ctx: &mut EvalContext) -> Result<bool, String> {
-    let brace = block.find('{').ok_or("if: missing '{' in block")?;
-    let end_brace = block.rfind('}').ok_or("if: missing '}' in block")?;
-    let header = block[..brace].trim();
-    let body = &block[brace + 1..end_brace];
+    match select_branch(block, ctx)? {
+        Some(body) => crate::dsl::runtime::exec_block(shapes, body, ctx),
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::evaluator::Value;
+
+    const CHAIN: &str = r#"if frame < 10 { "early" } else if frame < 20 and mode == "fast" { "mid" } else { "late" }"#;
+
+    fn branch(frame: f32, mode: &str) -> Option<String> {
+        let mut ctx = EvalContext::new().with_var("frame", frame);
+        ctx.set_var("mode", Value::Str(mode.into()));
+        select_branch(CHAIN, &ctx)
+            .unwrap()
+            .map(|body| body.trim().to_string())
+    }
+
+    #[test]
+    fn picks_the_first_matching_branch() {
+        assert_eq!(branch(5.0, "fast").as_deref(), Some("\"early\""));
+        assert_eq!(branch(15.0, "fast").as_deref(), Some("\"mid\""));
+        assert_eq!(branch(15.0, "slow").as_deref(), Some("\"late\""));
+        assert_eq!(branch(25.0, "fast").as_deref(), Some("\"late\""));
 
-    let mut invert = false;
-    let mut cond = header
-        .strip_prefix("if")
-        .ok_or("if: invalid header")?
-        .trim();
-    if cond.starts_with("not ") {
-        invert = true;
-        cond = cond.trim_start_matches("not ").trim();
+        let ctx = EvalContext::new().with_var("frame", 3.0);
+        assert_eq!(
+            select_branch("if not frame > 5 { a }", &ctx),
+            Ok(Some(" a "))
+        );
+        assert_eq!(select_branch("if frame > 5 { a { b } }", &ctx), Ok(None));
+        assert_eq!(
+            select_branch("if frame > 5 { a } elseif { b }", &ctx),
+            Err("if: expected 'else' or end of block".to_string())
+        );
     }
 
-    let res = eval_condition(cond, ctx)?;
-    let cond_true = if invert { !res } else { res };
-    if cond_true {
-        let modified = crate::dsl::runtime::exec_block(shapes, body, ctx)?;
-        return Ok(modified);
+    #[test]
+    fn conditions_must_be_boolean() {
+        let ctx = EvalContext::new().with_var("frame", 3.0);
+        assert_eq!(
+            select_branch("if frame { a }", &ctx),
+            Err("if: Expected a boolean, found number at offset 3".to_string())
+        );
+        // compile errors in untaken branches are still reported, at their
+        // position in the block
+        assert_eq!(
+            select_branch("if true { a } else if frame + { b }", &ctx),
+            Err("if: Unexpected end of expression at offset 30".to_string())
+        );
     }
-    Ok(false)
 }
@@ -6,14 +6,17 @@
 mod app_state;
 mod canvas;
 mod code_panel;
-mod dsl;
+mod events;
+mod logics;
 mod logo;
 mod modals;
-mod scene;
+mod scene_graph;
 mod states;
 mod timeline;
 mod ui;
 
+use motioner_core::{dsl, export, project, renderer, sampling, scene, timing};
+
 use anyhow::Result;
 use display_info::DisplayInfo;
 use eframe::egui;
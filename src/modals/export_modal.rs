@@ -0,0 +1,254 @@
+use crate::app_state::AppState;
+use crate::export::video::{RateControl, VideoCodec, VideoSettings};
+use crate::export::{ExportFormat, ExportJob, ExportOutcome, ExportSettings, LoopSettings};
+use crate::project::ExportPreset;
+use eframe::egui;
+
+/// Export dialog: pick a format, start the job, watch its progress and
+/// cancel it.  The job keeps running if the dialog is closed.
+pub fn show(ctx: &egui::Context, state: &mut AppState) {
+    finish_job_if_done(state);
+
+    let mut open = state.show_export;
+    egui::Window::new("Export")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            let running = state.export_job.is_some();
+
+            ui.add_enabled_ui(!running, |ui| {
+                presets_ui(ui, state);
+                egui::ComboBox::from_label("Format")
+                    .selected_text(state.export_format.label())
+                    .show_ui(ui, |ui| {
+                        for format in ExportFormat::ALL {
+                            ui.selectable_value(&mut state.export_format, *format, format.label());
+                        }
+                    });
+                if let ExportFormat::Video(codec) = state.export_format {
+                    ui.add_enabled_ui(codec.uses_rate_control(), |ui| {
+                        rate_control_ui(ui, &mut state.export_video.rate, codec);
+                    });
+                    ffmpeg_path_ui(ui, &mut state.export_video);
+                }
+                if state.export_format.loops() {
+                    loop_ui(ui, &mut state.export_loop, state.export_format);
+                }
+            });
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} x {} · {} fps · {:.2} s",
+                    state.render_width, state.render_height, state.fps, state.duration_secs
+                ))
+                .weak(),
+            );
+            ui.add_space(8.0);
+
+            if let Some(job) = &state.export_job {
+                let (done, total) = job.progress.frames();
+                ui.add(
+                    egui::ProgressBar::new(job.progress.fraction())
+                        .text(format!("Frame {} / {}", done, total)),
+                );
+                let cancelling = job.progress.is_cancelled();
+                if ui
+                    .add_enabled(!cancelling, egui::Button::new("Cancel"))
+                    .clicked()
+                {
+                    job.progress.cancel();
+                }
+                ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            } else {
+                match &state.project_path {
+                    Some(project) => {
+                        ui.label(format!(
+                            "Output: {}",
+                            state.export_format.output_path(project).to_string_lossy()
+                        ));
+                        if ui.button("Export").clicked() {
+                            let settings = ExportSettings {
+                                width: state.render_width,
+                                height: state.render_height,
+                                fps: state.fps,
+                                duration: state.duration_secs,
+                            };
+                            state.export_job = Some(ExportJob::start(
+                                state.export_format,
+                                state.scene.clone(),
+                                settings,
+                                state.export_video.clone(),
+                                state.export_loop,
+                                project.clone(),
+                            ));
+                            state.export_message = None;
+                        }
+                    }
+                    None => {
+                        ui.label("Open a project folder to export.");
+                    }
+                }
+            }
+
+            if let Some(message) = &state.export_message {
+                ui.add_space(8.0);
+                ui.label(message);
+            }
+        });
+    state.show_export = open;
+}
+
+/// Apply a preset from the project manifest, or save the current options
+/// as one.
+fn presets_ui(ui: &mut egui::Ui, state: &mut AppState) {
+    let (Some(dir), Some(manifest)) = (&state.project_path, &mut state.project_manifest) else {
+        return;
+    };
+    let mut chosen = None;
+    egui::ComboBox::from_id_source("export_preset")
+        .selected_text("Presets")
+        .show_ui(ui, |ui| {
+            for preset in &manifest.export_presets {
+                if ui.selectable_label(false, &preset.name).clicked() {
+                    chosen = Some(preset.clone());
+                }
+            }
+        });
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.export_preset_name)
+                .hint_text("Preset name")
+                .desired_width(140.0),
+        );
+        let name = state.export_preset_name.trim();
+        let taken = manifest.export_presets.iter().any(|p| p.name == name);
+        let save = ui
+            .add_enabled(!name.is_empty() && !taken, egui::Button::new("Save preset"))
+            .on_hover_text("Store these options in motioner.project")
+            .on_disabled_hover_text(if taken {
+                "A preset with this name already exists"
+            } else {
+                "Type a name for the preset"
+            });
+        if save.clicked() {
+            let preset = ExportPreset {
+                name: name.to_string(),
+                format: state.export_format,
+                video: state.export_video.clone(),
+                looping: state.export_loop,
+            };
+            state.export_message = match manifest
+                .add_export_preset(preset)
+                .and_then(|()| manifest.save(dir))
+            {
+                Ok(()) => {
+                    state.export_preset_name.clear();
+                    None
+                }
+                Err(err) => Some(format!("Could not save the preset: {:#}", err)),
+            };
+        }
+    });
+
+    if let Some(preset) = chosen {
+        state.export_format = preset.format;
+        // the ffmpeg location is a property of this machine, not the preset
+        let ffmpeg_path = state.export_video.ffmpeg_path.take();
+        state.export_video = preset.video;
+        if state.export_video.ffmpeg_path.is_none() {
+            state.export_video.ffmpeg_path = ffmpeg_path;
+        }
+        state.export_loop = preset.looping;
+    }
+}
+
+/// Rate control for `codec`; the CRF range follows the encoder.
+fn rate_control_ui(ui: &mut egui::Ui, rate: &mut RateControl, codec: VideoCodec) {
+    ui.horizontal(|ui| {
+        if ui
+            .radio(matches!(rate, RateControl::Crf(_)), "Quality (CRF)")
+            .clicked()
+        {
+            *rate = RateControl::Crf(20);
+        }
+        if ui
+            .radio(matches!(rate, RateControl::Bitrate(_)), "Bitrate")
+            .clicked()
+        {
+            *rate = RateControl::Bitrate(8000);
+        }
+        match rate {
+            RateControl::Crf(crf) => {
+                // a value kept from another codec may be out of range
+                *crf = (*crf).min(codec.max_crf());
+                ui.add(egui::DragValue::new(crf).clamp_range(0..=codec.max_crf()));
+            }
+            RateControl::Bitrate(kbps) => {
+                ui.add(
+                    egui::DragValue::new(kbps)
+                        .clamp_range(100..=200_000)
+                        .suffix(" kbit/s"),
+                );
+            }
+        }
+    });
+}
+
+fn loop_ui(ui: &mut egui::Ui, looping: &mut LoopSettings, format: ExportFormat) {
+    ui.horizontal(|ui| {
+        let mut forever = looping.loop_count == 0;
+        if ui.checkbox(&mut forever, "Loop forever").changed() {
+            looping.loop_count = if forever { 0 } else { 1 };
+        }
+        if !forever {
+            ui.add(
+                egui::DragValue::new(&mut looping.loop_count)
+                    .clamp_range(1..=u16::MAX)
+                    .suffix(" plays"),
+            );
+        }
+    });
+    if format == ExportFormat::Gif {
+        ui.checkbox(&mut looping.dither, "Dither colors");
+    }
+}
+
+/// Optional ffmpeg location; empty means "search PATH".
+fn ffmpeg_path_ui(ui: &mut egui::Ui, video: &mut VideoSettings) {
+    ui.horizontal(|ui| {
+        ui.label("ffmpeg");
+        let mut text = video
+            .ffmpeg_path
+            .as_ref()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let edit = egui::TextEdit::singleline(&mut text).hint_text("found on PATH");
+        if ui.add(edit).changed() {
+            let text = text.trim();
+            video.ffmpeg_path = (!text.is_empty()).then(|| text.into());
+        }
+    });
+}
+
+fn finish_job_if_done(state: &mut AppState) {
+    if !state
+        .export_job
+        .as_ref()
+        .is_some_and(ExportJob::is_finished)
+    {
+        return;
+    }
+    let Some(job) = state.export_job.take() else {
+        return;
+    };
+    state.export_message = Some(match job.join() {
+        Ok(ExportOutcome::Finished { frames, path }) => {
+            format!("Exported {} frames to {}", frames, path.to_string_lossy())
+        }
+        Ok(ExportOutcome::Cancelled { frames }) => {
+            format!("Export cancelled after {} frames", frames)
+        }
+        Err(err) => format!("Export failed: {:#}", err),
+    });
+}
@@ -0,0 +1,85 @@
+use crate::app_state::AppState;
+use crate::states::file_watch::{self, DiffLine};
+use eframe::egui;
+
+/// Prompt shown when the entry file changed on disk while the editor had
+/// unsaved changes: reload the file, keep the editor contents, or compare
+/// the two first.
+pub fn show(ctx: &egui::Context, state: &mut AppState) {
+    let file = state.entry_file().unwrap_or_default();
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let Some(conflict) = state.file_watch.conflict.as_mut() else {
+        return;
+    };
+    let now = ctx.input(|i| i.time);
+
+    enum Choice {
+        Reload,
+        Keep,
+    }
+    let mut choice = None;
+    egui::Window::new("File changed on disk")
+        .collapsible(false)
+        .resizable(conflict.show_diff)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{} was changed by another program, and the editor has changes that are not saved yet.",
+                name
+            ));
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Reload")
+                    .on_hover_text("Discard the editor changes (Ctrl+Z brings them back)")
+                    .clicked()
+                {
+                    choice = Some(Choice::Reload);
+                }
+                if ui
+                    .button("Keep mine")
+                    .on_hover_text("Overwrite the file with the editor contents")
+                    .clicked()
+                {
+                    choice = Some(Choice::Keep);
+                }
+                ui.toggle_value(&mut conflict.show_diff, "Diff");
+            });
+
+            if conflict.show_diff {
+                ui.add_space(8.0);
+                diff_view(ui, &state.dsl_code, &conflict.disk_text);
+            }
+        });
+
+    match choice {
+        Some(Choice::Reload) => {
+            if let Some(conflict) = state.file_watch.conflict.take() {
+                file_watch::reload(state, conflict.disk_text, now);
+            }
+        }
+        Some(Choice::Keep) => file_watch::keep_mine(state, now),
+        None => {}
+    }
+}
+
+/// Editor contents (`-`) against the file on disk (`+`).
+fn diff_view(ui: &mut egui::Ui, mine: &str, disk: &str) {
+    let removed = egui::Color32::from_rgb(230, 110, 110);
+    let added = egui::Color32::from_rgb(120, 200, 120);
+    egui::ScrollArea::both().max_height(320.0).show(ui, |ui| {
+        for line in file_watch::diff_lines(mine, disk) {
+            let (prefix, text, color) = match line {
+                DiffLine::Same(text) => (' ', text, ui.visuals().weak_text_color()),
+                DiffLine::Removed(text) => ('-', text, removed),
+                DiffLine::Added(text) => ('+', text, added),
+            };
+            ui.label(
+                egui::RichText::new(format!("{} {}", prefix, text))
+                    .monospace()
+                    .color(color),
+            );
+        }
+    });
+}
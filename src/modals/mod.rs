@@ -1,2 +1,4 @@
+pub mod export_modal;
+pub mod file_conflict_modal;
 pub mod welcome_modal;
 pub mod project_settings;
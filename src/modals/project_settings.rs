@@ -8,6 +8,12 @@ pub fn show(ctx: &egui::Context, state: &mut AppState) {
         state.settings_open_time = Some(now);
     }
 
+    // Pick up header edits still waiting for the debounced parse, so the
+    // fields below never write stale values back into the code.
+    if state.scene_parse_pending.take().is_some() {
+        state.parse_scene(now);
+    }
+
     // 2. Calculate animation progress (t)
     let start_time = state.settings_open_time.unwrap();
     let duration = if state.settings_is_closing { 0.25 } else { 0.3 };
@@ -108,7 +114,11 @@ pub fn show(ctx: &egui::Context, state: &mut AppState) {
                         .max_height(window_height - 100.0) // Leave room for header/footer
                         .show(ui, |ui| {
                             ui.add_space(20.0);
+                            let before = state.header_config();
                             render_body(ui, state);
+                            if state.header_config() != before {
+                                state.sync_header_to_code(now);
+                            }
                             ui.add_space(30.0);
                         });
 
@@ -241,9 +251,17 @@ fn render_body(ui: &mut egui::Ui, state: &mut AppState) {
                 .show(ui, |ui| {
                     ui.label(egui::RichText::new("Dimensions").color(label_color));
                     ui.horizontal(|ui| {
-                        ui.add(egui::DragValue::new(&mut state.render_width).prefix("W: "));
+                        ui.add(
+                            egui::DragValue::new(&mut state.render_width)
+                                .clamp_range(1..=16384)
+                                .prefix("W: "),
+                        );
                         ui.label(egui::RichText::new("x").color(label_color));
-                        ui.add(egui::DragValue::new(&mut state.render_height).prefix("H: "));
+                        ui.add(
+                            egui::DragValue::new(&mut state.render_height)
+                                .clamp_range(1..=16384)
+                                .prefix("H: "),
+                        );
                     });
                     ui.end_row();
 
@@ -283,6 +301,31 @@ fn render_body(ui: &mut egui::Ui, state: &mut AppState) {
                     ui.end_row();
                 });
         });
+
+        ui.add_space(12.0);
+
+        // 3. Editor
+        section(ui, "Editor", &|ui, state| {
+            egui::Grid::new("editor_grid")
+                .num_columns(2)
+                .spacing([40.0, 16.0])
+                .show(ui, |ui| {
+                    const MIB: usize = 1024 * 1024;
+                    ui.label(egui::RichText::new("Undo Memory").color(label_color));
+                    ui.horizontal(|ui| {
+                        let mut mib = state.history.memory_limit / MIB;
+                        if ui
+                            .add(egui::DragValue::new(&mut mib).clamp_range(1..=1024))
+                            .changed()
+                        {
+                            state.history.memory_limit = mib * MIB;
+                            state.history.trim();
+                        }
+                        ui.label(egui::RichText::new("MB").color(label_color));
+                    });
+                    ui.end_row();
+                });
+        });
     });
 }
 
@@ -1,6 +1,10 @@
 use crate::app_state::AppState;
 use eframe::egui;
 
+/// Recent projects visible without scrolling.
+const RECENT_ROWS_SHOWN: usize = 4;
+const RECENT_ROW_HEIGHT: f32 = 30.0;
+
 pub fn show(ctx: &egui::Context, state: &mut AppState) {
     if !state.show_welcome {
         return;
@@ -32,7 +36,12 @@ pub fn show(ctx: &egui::Context, state: &mut AppState) {
         .interactable(true)
         .show(ctx, |ui| {
             let width = 680.0;
-            let height = 460.0;
+            let recent_rows = state.recent_projects.entries.len().min(RECENT_ROWS_SHOWN);
+            let height = if recent_rows == 0 {
+                460.0
+            } else {
+                500.0 + recent_rows as f32 * RECENT_ROW_HEIGHT
+            };
 
             // Outer glow / shadow effect done via shadow property
             let frame = egui::Frame::none()
@@ -248,16 +257,7 @@ pub fn show(ctx: &egui::Context, state: &mut AppState) {
 
                             if ui.add_enabled(is_ready, launch_btn).clicked() {
                                 let path = std::path::PathBuf::from(&state.project_path_input);
-                                if path.exists() && path.is_dir() {
-                                    state.project_path = Some(path);
-                                    state.refresh_fonts_async();
-                                    state.show_welcome = false;
-                                } else if !path.exists() {
-                                    state.path_validation_error =
-                                        Some("Path does not exist.".into());
-                                } else {
-                                    state.path_validation_error = Some("Not a directory.".into());
-                                }
+                                launch(ui, state, path);
                             }
 
                             if let Some(err) = &state.path_validation_error {
@@ -267,6 +267,11 @@ pub fn show(ctx: &egui::Context, state: &mut AppState) {
                                         .color(egui::Color32::from_rgb(255, 120, 120)),
                                 );
                             }
+
+                            if !state.recent_projects.entries.is_empty() {
+                                ui.add_space(20.0);
+                                recent_projects_list(ui, state);
+                            }
                         });
 
                         // Bottom text expanding to the bottom margin
@@ -281,3 +286,97 @@ pub fn show(ctx: &egui::Context, state: &mut AppState) {
             });
         });
 }
+
+/// Open the project folder at `path`, or explain why it can't be opened.
+fn launch(ui: &egui::Ui, state: &mut AppState, path: std::path::PathBuf) {
+    if !path.exists() {
+        state.path_validation_error = Some("Path does not exist.".into());
+    } else if !path.is_dir() {
+        state.path_validation_error = Some("Not a directory.".into());
+    } else {
+        let now = ui.input(|i| i.time);
+        match state.open_project(path, now) {
+            Ok(()) => {
+                state.path_validation_error = None;
+                state.refresh_fonts_async();
+                state.show_welcome = false;
+            }
+            Err(err) => {
+                state.path_validation_error = Some(format!("Could not open project: {:#}", err));
+            }
+        }
+    }
+}
+
+/// Recently opened projects; missing folders are shown but can't be opened.
+fn recent_projects_list(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new("RECENT PROJECTS")
+                .size(11.0)
+                .strong()
+                .color(egui::Color32::from_rgb(140, 160, 255))
+                .extra_letter_spacing(1.0),
+        );
+    });
+    ui.add_space(6.0);
+
+    let mut open = None;
+    let mut remove = None;
+    egui::ScrollArea::vertical()
+        .max_height(RECENT_ROWS_SHOWN as f32 * RECENT_ROW_HEIGHT)
+        .show(ui, |ui| {
+            for entry in &state.recent_projects.entries {
+                let missing = entry.is_missing();
+                ui.horizontal(|ui| {
+                    ui.set_height(RECENT_ROW_HEIGHT - 4.0);
+                    let name = if entry.name.is_empty() {
+                        entry.path.to_string_lossy().into_owned()
+                    } else {
+                        entry.name.clone()
+                    };
+                    let text = egui::RichText::new(name).size(14.0).color(if missing {
+                        egui::Color32::from_gray(110)
+                    } else {
+                        egui::Color32::WHITE
+                    });
+                    let response = ui
+                        .add_enabled(!missing, egui::Button::new(text).frame(false))
+                        .on_hover_text(entry.path.to_string_lossy())
+                        .on_disabled_hover_text(format!(
+                            "{} no longer exists",
+                            entry.path.to_string_lossy()
+                        ));
+                    if response.clicked() {
+                        open = Some(entry.path.clone());
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .small_button("✕")
+                            .on_hover_text("Remove from the list")
+                            .clicked()
+                        {
+                            remove = Some(entry.path.clone());
+                        }
+                        let detail = if missing {
+                            egui::RichText::new("Missing")
+                                .color(egui::Color32::from_rgb(255, 120, 120))
+                        } else {
+                            egui::RichText::new(entry.path.to_string_lossy())
+                                .color(egui::Color32::from_gray(120))
+                        };
+                        ui.add(egui::Label::new(detail.size(11.0)).truncate(true));
+                    });
+                });
+            }
+        });
+
+    if let Some(path) = remove {
+        state.recent_projects.remove(&path);
+        let _ = state.recent_projects.save();
+    }
+    if let Some(path) = open {
+        state.project_path_input = path.to_string_lossy().into_owned();
+        launch(ui, state, path);
+    }
+}
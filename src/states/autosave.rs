@@ -1,4 +1,22 @@
-#[derive(Debug, Default)]
+//! Autosave of the DSL code to the project's entry file (`code.motioner`
+//! unless the manifest says otherwise).
+//!
+//! Edits mark the state dirty; once no edit has happened for
+//! `cooldown_secs`, [`tick`] validates the code and writes it.  Invalid code
+//! is never written.  The file is replaced atomically (temp file + rename)
+//! and a few older versions are kept as `code.motioner.N.bak`.
+
+use crate::app_state::AppState;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How many `.bak` versions to keep.
+pub const BACKUP_COUNT: usize = 5;
+/// Minimum age of the newest backup before another is taken, so that a
+/// burst of autosaves does not push the whole history out.
+pub const BACKUP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug)]
 pub struct AutosaveState {
     pub last_edit_time: Option<f64>,
     pub pending: bool,
@@ -7,30 +25,75 @@ pub struct AutosaveState {
     pub cooldown_secs: f32,
 }
 
+impl Default for AutosaveState {
+    fn default() -> Self {
+        Self {
+            last_edit_time: None,
+            pending: false,
+            last_success_time: None,
+            error: None,
+            cooldown_secs: 1.0,
+        }
+    }
+}
+
 pub fn apply_diagnostics(
     state: &mut crate::app_state::AppState,
     diags: Vec<crate::dsl::Diagnostic>,
 ) {
-    if diags.is_empty() {
-        state.dsl.diagnostics.clear();
-        state.autosave.error = None;
-    } else {
-        state.dsl.diagnostics = diags.clone();
-        state.autosave.pending = false;
-        state.autosave.error = Some(diags[0].message.clone());
+    match diags.iter().find(|d| d.is_error()) {
+        None => state.autosave.error = None,
+        Some(first) => {
+            state.autosave.pending = false;
+            state.autosave.error = Some(first.message.clone());
+        }
     }
+    state.dsl.diagnostics = diags;
 }
 
 pub fn tick(state: &mut crate::app_state::AppState, now: f64) {
     if let Some(last_edit) = state.autosave.last_edit_time {
         if now - last_edit > state.autosave.cooldown_secs as f64 {
-            let diagnostics = crate::dsl::utils::validate_and_normalize(&mut state.dsl_code);
-            apply_diagnostics(state, diagnostics);
+            save_now(state, now);
+        }
+    }
+}
+
+/// Validate and write the code right away, recording the outcome on
+/// `state.autosave`.  A failed write is retried after another cooldown.
+pub fn save_now(state: &mut AppState, now: f64) {
+    if state.file_watch.conflict.is_some() {
+        // the file changed on disk too; wait until the user picks a side
+        state.autosave.last_edit_time = Some(now);
+        return;
+    }
+    state.autosave.last_edit_time = None;
+    if state.project_path.is_none() {
+        // nowhere to save yet; still surface the diagnostics
+        let diagnostics = crate::dsl::utils::validate_and_normalize(&mut state.dsl_code);
+        state.history.amend(&state.dsl_code);
+        apply_diagnostics(state, diagnostics);
+        return;
+    }
 
-            // In factory state, "saving" is just clearing the dirty flag
+    let written = crate::events::element_properties_changed_event::write_dsl_to_project(state);
+    // normalizing the indentation is not a step of its own
+    state.history.amend(&state.dsl_code);
+    match written {
+        Ok(()) => {
             state.autosave.pending = false;
+            state.autosave.error = None;
             state.autosave.last_success_time = Some(now);
-            state.autosave.last_edit_time = None;
+        }
+        Err(_) if state.dsl.diagnostics.iter().any(|d| d.is_error()) => {
+            let diagnostics = std::mem::take(&mut state.dsl.diagnostics);
+            apply_diagnostics(state, diagnostics);
+        }
+        Err(err) => {
+            let file = state.entry_file().unwrap_or_default();
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            state.autosave.error = Some(format!("Could not save {}: {}", name, err));
+            state.autosave.last_edit_time = Some(now);
         }
     }
 }
@@ -41,3 +104,123 @@ impl AutosaveState {
         self.pending = true;
     }
 }
+
+// ─── Files ──────────────────────────────────────────────────────────────────
+
+/// `<path>.<n>.bak`; `n = 1` is the newest backup.
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", n));
+    path.with_file_name(name)
+}
+
+/// Copy the current `path` to the newest backup slot, shifting older
+/// backups down and dropping the one past `keep`.  Skipped when `path` does
+/// not exist or the newest backup is younger than `min_age`.
+pub fn rotate_backups(path: &Path, keep: usize, min_age: Duration) -> std::io::Result<()> {
+    if keep == 0 || !path.is_file() {
+        return Ok(());
+    }
+    let newest = backup_path(path, 1);
+    if let Ok(modified) = std::fs::metadata(&newest).and_then(|m| m.modified()) {
+        if modified.elapsed().is_ok_and(|age| age < min_age) {
+            return Ok(());
+        }
+    }
+
+    let _ = std::fs::remove_file(backup_path(path, keep));
+    for n in (1..keep).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            std::fs::rename(&from, backup_path(path, n + 1))?;
+        }
+    }
+    std::fs::copy(path, &newest)?;
+    // the age check above relies on the backup's own time, not the source's
+    std::fs::File::options()
+        .write(true)
+        .open(&newest)?
+        .set_modified(SystemTime::now())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{write_atomic, DEFAULT_ENTRY};
+
+    const VALID: &str = "size(64, 64)\ntimeline(fps = 10, duration = 1)\n";
+
+    #[test]
+    fn writes_atomically_and_rotates_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(DEFAULT_ENTRY);
+
+        for version in 1..=4 {
+            rotate_backups(&file, 2, Duration::ZERO).unwrap();
+            write_atomic(&file, format!("v{}", version).as_bytes()).unwrap();
+        }
+        let read = |p: PathBuf| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(file.clone()), "v4");
+        assert_eq!(read(backup_path(&file, 1)), "v3");
+        assert_eq!(read(backup_path(&file, 2)), "v2");
+        assert!(!backup_path(&file, 3).exists());
+
+        // a fresh backup is not replaced within the interval
+        rotate_backups(&file, 2, Duration::from_secs(60)).unwrap();
+        assert_eq!(read(backup_path(&file, 1)), "v3");
+
+        // no temp files are left behind
+        let entries = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(entries, 3);
+    }
+
+    #[test]
+    fn tick_saves_after_the_cooldown_and_reports_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState {
+            project_path: Some(dir.path().to_path_buf()),
+            ..AppState::default()
+        };
+        let file = dir.path().join(DEFAULT_ENTRY);
+
+        state.dsl_code = VALID.to_string();
+        state.autosave.mark_dirty(10.0);
+        tick(&mut state, 10.5);
+        assert!(!file.exists(), "saved before the cooldown");
+        tick(&mut state, 11.5);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), VALID);
+        assert_eq!(state.autosave.last_success_time, Some(11.5));
+        assert!(!state.autosave.pending);
+
+        // invalid code is reported, not written
+        state.dsl_code = format!("{}rect \"A\" {{ x = \"left\" }}\n", VALID);
+        state.autosave.mark_dirty(20.0);
+        tick(&mut state, 22.0);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), VALID);
+        assert!(state.autosave.error.is_some());
+        assert!(!state.dsl.diagnostics.is_empty());
+
+        // a file without a header is saved, with a warning
+        let headerless = "rect \"A\" {}\n";
+        state.dsl_code = headerless.to_string();
+        state.autosave.mark_dirty(25.0);
+        tick(&mut state, 27.0);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), headerless);
+        assert!(state.autosave.error.is_none());
+
+        // an unwritable project keeps the edit pending and says why
+        state.dsl_code = VALID.to_string();
+        state.project_path = Some(dir.path().join("missing"));
+        state.autosave.mark_dirty(30.0);
+        tick(&mut state, 32.0);
+        let error = state.autosave.error.clone().unwrap();
+        assert!(
+            error.starts_with("Could not save code.motioner"),
+            "{}",
+            error
+        );
+        assert!(state.autosave.pending);
+        assert_eq!(state.autosave.last_edit_time, Some(32.0));
+    }
+}
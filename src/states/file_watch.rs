@@ -0,0 +1,255 @@
+//! Hot reload of the project's entry file when it is edited outside the app.
+//!
+//! The file is polled (size and modification time, then the contents)
+//! rather than watched through OS notifications, so there is nothing
+//! platform-specific to set up.  A change on disk is applied right away
+//! when the editor has no unsaved changes; otherwise it is held as a
+//! [`Conflict`] for the user to resolve, and autosave is paused meanwhile so
+//! neither version is lost.
+//!
+//! Modification times are coarse on some file systems, so a same-size edit
+//! right after a save can leave the stamp unchanged.  The stamp is therefore
+//! only trusted once the contents were compared well after it.
+
+use crate::app_state::AppState;
+use crate::states::history::EditKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Seconds between two looks at the file.
+pub const POLL_INTERVAL_SECS: f64 = 0.5;
+
+/// Coarsest modification time resolution to expect (FAT).
+const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+
+/// The file changed on disk while the editor had unsaved changes.
+#[derive(Debug)]
+pub struct Conflict {
+    /// The new contents of the file.
+    pub disk_text: String,
+    /// The user asked to see the line diff.
+    pub show_diff: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct FileWatchState {
+    path: Option<PathBuf>,
+    /// Size and modification time at the last look.
+    stamp: Option<(u64, SystemTime)>,
+    /// When `disk_text` was last known to match the file.
+    checked: Option<SystemTime>,
+    /// Contents last loaded from or written to `path`.
+    disk_text: String,
+    next_poll: f64,
+    pub conflict: Option<Conflict>,
+}
+
+fn stamp(path: &Path) -> Option<(u64, SystemTime)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.len(), meta.modified().ok()?))
+}
+
+impl FileWatchState {
+    /// Start watching `path`, whose contents are `text`.
+    pub fn watch(&mut self, path: PathBuf, text: &str) {
+        self.stamp = stamp(&path);
+        self.checked = Some(SystemTime::now());
+        self.path = Some(path);
+        self.disk_text = text.to_string();
+        self.conflict = None;
+    }
+
+    /// Remember a write made by the app itself so it is not taken for an
+    /// external edit.
+    pub fn record_write(&mut self, text: &str) {
+        if let Some(path) = &self.path {
+            self.stamp = stamp(path);
+        }
+        self.checked = Some(SystemTime::now());
+        self.disk_text = text.to_string();
+    }
+
+    /// `code` differs from what is on disk.
+    pub fn is_unsaved(&self, code: &str) -> bool {
+        code != self.disk_text
+    }
+
+    /// Look at the file if the poll interval has passed; returns its new
+    /// contents when someone else changed it.
+    pub fn poll(&mut self, now: f64) -> Option<String> {
+        if now < self.next_poll {
+            return None;
+        }
+        self.next_poll = now + POLL_INTERVAL_SECS;
+        let path = self.path.as_ref()?;
+        let current = stamp(path)?;
+        // an edit within the mtime resolution of the last check may have
+        // left the stamp as it was
+        let settled = self
+            .checked
+            .and_then(|checked| checked.duration_since(current.1).ok())
+            .is_some_and(|age| age >= MTIME_GRANULARITY);
+        if self.stamp == Some(current) && settled {
+            return None;
+        }
+        self.stamp = Some(current);
+        let checked = SystemTime::now();
+        // an editor may touch the file without changing it
+        let text = std::fs::read_to_string(path).ok()?;
+        self.checked = Some(checked);
+        if text == self.disk_text {
+            return None;
+        }
+        self.disk_text = text.clone();
+        Some(text)
+    }
+}
+
+/// Poll the entry file and reload it, or raise a conflict.
+pub fn tick(state: &mut AppState, now: f64) {
+    let unsaved = state.file_watch.is_unsaved(&state.dsl_code);
+    let Some(text) = state.file_watch.poll(now) else {
+        return;
+    };
+    if text == state.dsl_code {
+        state.file_watch.conflict = None;
+    } else if unsaved {
+        state.file_watch.conflict = Some(Conflict {
+            disk_text: text,
+            show_diff: false,
+        });
+    } else {
+        reload(state, text, now);
+    }
+}
+
+/// Replace the editor contents with `text` from disk.  The reload is an
+/// undo step, so the previous code is one Ctrl+Z away.
+pub fn reload(state: &mut AppState, text: String, now: f64) {
+    state.file_watch.conflict = None;
+    state.dsl_code = text;
+    state
+        .history
+        .commit(&state.dsl_code, EditKind::Structural, now);
+    state.autosave.last_edit_time = None;
+    state.autosave.pending = false;
+    state.scene_parse_pending = None;
+    state.parse_scene(now);
+}
+
+/// Keep the editor contents; they overwrite the file on the next save.
+pub fn keep_mine(state: &mut AppState, now: f64) {
+    state.file_watch.conflict = None;
+    state.autosave.mark_dirty(now);
+}
+
+// ─── Diff ───────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line diff turning `old` into `new` (longest common subsequence).
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    // lcs[i][j]: common lines of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push(DiffLine::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(DiffLine::Removed(a[i]));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|l| DiffLine::Removed(l)));
+    out.extend(b[j..].iter().map(|l| DiffLine::Added(l)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reloads_external_edits_and_flags_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("code.motioner");
+        std::fs::write(&file, "size(8, 8)\n").unwrap();
+
+        let mut state = AppState {
+            dsl_code: "size(8, 8)\n".to_string(),
+            ..AppState::default()
+        };
+        state.file_watch.watch(file.clone(), &state.dsl_code);
+
+        // our own save is not an external change
+        std::fs::write(&file, "size(8, 9)\n").unwrap();
+        state.dsl_code = "size(8, 9)\n".to_string();
+        state.file_watch.record_write(&state.dsl_code);
+        tick(&mut state, 1.0);
+        assert!(state.file_watch.conflict.is_none());
+
+        let version = state.scene_version;
+        std::fs::write(&file, "size(16, 16)\n").unwrap();
+        tick(&mut state, 2.0);
+        assert_eq!(state.dsl_code, "size(16, 16)\n");
+        assert!(state.scene_version > version);
+        assert!(state.file_watch.conflict.is_none());
+
+        state.dsl_code.push_str("// mine\n");
+        std::fs::write(&file, "size(32, 32)\n").unwrap();
+        tick(&mut state, 3.0);
+        let conflict = state.file_watch.conflict.as_ref().unwrap();
+        assert_eq!(conflict.disk_text, "size(32, 32)\n");
+        assert!(state.dsl_code.ends_with("// mine\n"));
+    }
+
+    #[test]
+    fn sees_same_size_edits_with_an_unchanged_stamp() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("code.motioner");
+        std::fs::write(&file, "size(8, 8)\n").unwrap();
+        let mut watch = FileWatchState::default();
+        watch.watch(file.clone(), "size(8, 8)\n");
+        let mtime = std::fs::metadata(&file).unwrap().modified().unwrap();
+
+        // same length and, as on a coarse file system, the same mtime
+        std::fs::write(&file, "size(9, 9)\n").unwrap();
+        let handle = std::fs::File::options().write(true).open(&file).unwrap();
+        handle.set_modified(mtime).unwrap();
+        assert_eq!(stamp(&file), watch.stamp);
+        assert_eq!(watch.poll(1.0), Some("size(9, 9)\n".to_string()));
+        assert_eq!(watch.poll(2.0), None);
+    }
+
+    #[test]
+    fn diffs_lines() {
+        use DiffLine::*;
+        assert_eq!(
+            diff_lines("a\nb\nc\n", "a\nc\nd\n"),
+            vec![Same("a"), Removed("b"), Same("c"), Added("d")]
+        );
+    }
+}
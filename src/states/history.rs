@@ -0,0 +1,215 @@
+//! Application-level undo/redo for the DSL code.
+//!
+//! Every panel edits the scene through `dsl_code` (the settings modal
+//! rewrites its header, structural edits regenerate it), so the history
+//! stores whole snapshots of the code rather than per-widget commands.
+//! Editors report each change with [`History::commit`]; consecutive typing
+//! (or dragging a value) is merged into one step until the user pauses.
+//! Old steps are dropped once the snapshots exceed the memory limit.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Default limit for the stored snapshots.
+pub const DEFAULT_MEMORY_LIMIT: usize = 32 * 1024 * 1024;
+/// A pause longer than this ends a run of merged edits.
+pub const MERGE_WINDOW_SECS: f64 = 1.0;
+
+/// How an edit was made; decides whether it merges with the previous one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditKind {
+    /// Keystrokes in the code editor.
+    Typing,
+    /// Continuous widget edits such as settings values.
+    Drag,
+    /// One-shot changes such as a scene-graph reorder; always a step of
+    /// their own.
+    Structural,
+}
+
+impl EditKind {
+    fn merges(self) -> bool {
+        self != EditKind::Structural
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct History {
+    /// Upper bound, in bytes, for the snapshots kept in both stacks.
+    pub memory_limit: usize,
+    /// Oldest first.
+    #[serde(skip)]
+    undo: VecDeque<String>,
+    #[serde(skip)]
+    redo: Vec<String>,
+    /// The code as of the last commit, undo or redo.
+    #[serde(skip)]
+    current: String,
+    /// Kind and time of the last commit while a merge run is open.
+    #[serde(skip)]
+    run: Option<(EditKind, f64)>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            current: String::new(),
+            run: None,
+        }
+    }
+}
+
+impl History {
+    /// Forget every step and start from `code` (e.g. a newly opened project).
+    pub fn reset(&mut self, code: &str) {
+        self.undo.clear();
+        self.redo.clear();
+        self.current = code.to_string();
+        self.run = None;
+    }
+
+    /// Record that the code is now `code`.  Returns whether a new undo step
+    /// was created (merged and unchanged commits return `false`).
+    pub fn commit(&mut self, code: &str, kind: EditKind, now: f64) -> bool {
+        if code == self.current {
+            return false;
+        }
+        let merge = kind.merges()
+            && !self.undo.is_empty()
+            && self
+                .run
+                .is_some_and(|(last, at)| last == kind && now - at <= MERGE_WINDOW_SECS);
+        self.run = kind.merges().then_some((kind, now));
+        self.redo.clear();
+        let previous = std::mem::replace(&mut self.current, code.to_string());
+        if merge {
+            return false;
+        }
+        self.undo.push_back(previous);
+        self.trim();
+        true
+    }
+
+    /// Replace the current code without creating a step, for rewrites the
+    /// user did not make (indentation normalized on save).
+    pub fn amend(&mut self, code: &str) {
+        if code != self.current {
+            self.current = code.to_string();
+        }
+    }
+
+    /// The code before the last step, if there is one.
+    pub fn undo(&mut self) -> Option<String> {
+        let code = self.undo.pop_back()?;
+        self.redo
+            .push(std::mem::replace(&mut self.current, code.clone()));
+        self.run = None;
+        Some(code)
+    }
+
+    /// The code of the last undone step, if there is one.
+    pub fn redo(&mut self) -> Option<String> {
+        let code = self.redo.pop()?;
+        self.undo
+            .push_back(std::mem::replace(&mut self.current, code.clone()));
+        self.run = None;
+        self.trim();
+        Some(code)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Bytes held by the stored snapshots.
+    pub fn memory_used(&self) -> usize {
+        self.undo.iter().chain(&self.redo).map(String::len).sum()
+    }
+
+    /// Drop the oldest undo steps until the snapshots fit the limit.
+    pub fn trim(&mut self) {
+        let mut used = self.memory_used();
+        while used > self.memory_limit {
+            let Some(oldest) = self.undo.pop_front() else {
+                break;
+            };
+            used -= oldest.len();
+        }
+    }
+}
+
+// ─── Shortcuts ──────────────────────────────────────────────────────────────
+
+/// Handle Ctrl+Z / Ctrl+Shift+Z (Cmd on macOS; Ctrl+Y also redoes).
+///
+/// Must run before the panels so the code editor's own undo stack never
+/// sees the keys.
+pub fn handle_shortcuts(ctx: &egui::Context, state: &mut crate::app_state::AppState, now: f64) {
+    use egui::{Key, KeyboardShortcut, Modifiers};
+    let redo = KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z);
+    let redo_y = KeyboardShortcut::new(Modifiers::COMMAND, Key::Y);
+    let undo = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+
+    // most specific first: Cmd+Z also matches Cmd+Shift+Z
+    let (redo, undo) = ctx.input_mut(|i| {
+        let redo = i.consume_shortcut(&redo) | i.consume_shortcut(&redo_y);
+        (redo, i.consume_shortcut(&undo))
+    });
+    if undo {
+        state.undo(now);
+    }
+    if redo {
+        state.redo(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_typing_until_a_pause() {
+        let mut history = History::default();
+        history.reset("");
+        assert!(history.commit("a", EditKind::Typing, 0.0));
+        assert!(!history.commit("ab", EditKind::Typing, 0.5));
+        assert!(!history.commit("abc", EditKind::Typing, 1.2));
+        assert!(history.commit("abc d", EditKind::Typing, 3.0));
+        assert!(history.commit("abc d!", EditKind::Structural, 3.1));
+        assert!(history.commit("abc d!?", EditKind::Structural, 3.2));
+
+        assert_eq!(history.undo().as_deref(), Some("abc d!"));
+        assert_eq!(history.undo().as_deref(), Some("abc d"));
+        assert_eq!(history.undo().as_deref(), Some("abc"));
+        assert_eq!(history.undo().as_deref(), Some(""));
+        assert_eq!(history.undo(), None);
+
+        assert_eq!(history.redo().as_deref(), Some("abc"));
+        history.commit("x", EditKind::Typing, 10.0);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo().as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn drops_the_oldest_steps_over_the_memory_limit() {
+        let mut history = History {
+            memory_limit: 10,
+            ..History::default()
+        };
+        history.reset("");
+        for (i, code) in ["aaaa", "bbbb", "cccc", "dddd"].iter().enumerate() {
+            history.commit(code, EditKind::Structural, i as f64);
+        }
+        assert!(history.memory_used() <= 10);
+        assert_eq!(history.undo().as_deref(), Some("cccc"));
+        assert_eq!(history.undo().as_deref(), Some("bbbb"));
+        assert_eq!(history.undo(), None);
+    }
+}
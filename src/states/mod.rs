@@ -1,2 +1,21 @@
 pub mod autosave;
 pub mod dslstate;
+pub mod file_watch;
+pub mod history;
+pub mod recent_projects;
+pub mod view_state;
+
+/// Motioner's folder in the platform's per-user config directory.
+pub fn config_dir() -> Option<std::path::PathBuf> {
+    use std::path::PathBuf;
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|h| PathBuf::from(h).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+    };
+    base.map(|b| b.join("motioner"))
+}
@@ -0,0 +1,108 @@
+//! Recently opened projects, shown on the welcome screen.
+//!
+//! Stored as JSON in the user's config folder, independent of any project,
+//! so the list survives across sessions.  Entries whose folder has gone
+//! away are kept (and shown as missing) until the user removes them.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Longest list kept.
+pub const MAX_RECENT: usize = 10;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecentProject {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl RecentProject {
+    /// The folder was moved or deleted since it was last opened.
+    pub fn is_missing(&self) -> bool {
+        !self.path.is_dir()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RecentProjects {
+    /// Newest first.
+    pub entries: Vec<RecentProject>,
+    /// Where the list is saved; `None` keeps it in memory only.
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl RecentProjects {
+    /// Load the list from `file`.  A missing or unreadable file gives an
+    /// empty list that will be saved to `file`.
+    pub fn load(file: PathBuf) -> Self {
+        let mut recent: Self = std::fs::read_to_string(&file)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        recent.file = Some(file);
+        recent
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        crate::project::write_atomic(file, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Move `path` to the top of the list, adding it if needed.
+    pub fn touch(&mut self, name: &str, path: &Path) {
+        self.entries.retain(|e| e.path != path);
+        self.entries.insert(
+            0,
+            RecentProject {
+                name: name.to_string(),
+                path: path.to_path_buf(),
+            },
+        );
+        self.entries.truncate(MAX_RECENT);
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.retain(|e| e.path != path);
+    }
+}
+
+/// `recent_projects.json` in the platform's per-user config folder.
+pub fn default_file() -> Option<PathBuf> {
+    super::config_dir().map(|dir| dir.join("recent_projects.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_newest_first_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config").join("recent.json");
+        let mut recent = RecentProjects::load(file.clone());
+        assert!(recent.entries.is_empty());
+
+        for i in 0..MAX_RECENT + 2 {
+            recent.touch(&format!("p{}", i), &dir.path().join(format!("p{}", i)));
+        }
+        recent.touch("p3", &dir.path().join("p3"));
+        assert_eq!(recent.entries.len(), MAX_RECENT);
+        assert_eq!(recent.entries[0].name, "p3");
+        assert_eq!(recent.entries[1].name, format!("p{}", MAX_RECENT + 1));
+        recent.save().unwrap();
+
+        let loaded = RecentProjects::load(file);
+        assert_eq!(loaded.entries, recent.entries);
+        // none of the folders exist
+        assert!(loaded.entries.iter().all(RecentProject::is_missing));
+        std::fs::create_dir(dir.path().join("p3")).unwrap();
+        assert!(!loaded.entries[0].is_missing());
+    }
+}
@@ -0,0 +1,308 @@
+//! Layout and view state remembered between sessions.
+//!
+//! The panel layout is shared by every project, while the canvas and
+//! timeline pan/zoom are remembered per project folder.  Both are written
+//! as one JSON document through [`eframe::Storage`] (see `ui::MyApp`).  The
+//! document carries a schema version: a document that is newer than this
+//! build, or that does not parse, is ignored and the defaults are used.
+
+use crate::app_state::{AppState, PanelTab};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Key of the document in the storage.
+pub const STORAGE_KEY: &str = "motioner_view";
+/// Bump when a field changes meaning; added fields only need a default.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Panel layout shared by all projects.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Layout {
+    pub left_panel_width: f32,
+    pub active_tab: Option<PanelTab>,
+    pub last_active_tab: PanelTab,
+    pub transport_pos: Option<egui::Pos2>,
+    /// The "Undo Memory" setting, in bytes.
+    pub undo_memory_limit: usize,
+}
+
+impl Layout {
+    fn of(state: &AppState) -> Self {
+        Self {
+            left_panel_width: state.left_panel_width,
+            active_tab: state.active_tab,
+            last_active_tab: state.last_active_tab,
+            transport_pos: state.transport_pos,
+            undo_memory_limit: state.history.memory_limit,
+        }
+    }
+
+    fn apply(&self, state: &mut AppState) {
+        state.left_panel_width = self.left_panel_width;
+        state.active_tab = self.active_tab;
+        state.last_active_tab = self.last_active_tab;
+        state.transport_pos = self.transport_pos;
+        state.history.memory_limit = self.undo_memory_limit;
+    }
+}
+
+/// Matches a fresh `AppState`.
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            left_panel_width: 400.0,
+            active_tab: Some(PanelTab::Code),
+            last_active_tab: PanelTab::Code,
+            transport_pos: None,
+            undo_memory_limit: crate::states::history::DEFAULT_MEMORY_LIMIT,
+        }
+    }
+}
+
+/// Where a project's canvas and timeline were panned and zoomed to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectView {
+    pub canvas_pan_x: f32,
+    pub canvas_pan_y: f32,
+    pub canvas_zoom: f32,
+    pub timeline_pan_x: f32,
+    pub timeline_pan_y: f32,
+    pub timeline_zoom: f32,
+}
+
+impl ProjectView {
+    fn of(state: &AppState) -> Self {
+        Self {
+            canvas_pan_x: state.canvas_pan_x,
+            canvas_pan_y: state.canvas_pan_y,
+            canvas_zoom: state.canvas_zoom,
+            timeline_pan_x: state.timeline_pan_x,
+            timeline_pan_y: state.timeline_pan_y,
+            timeline_zoom: state.timeline_zoom,
+        }
+    }
+
+    fn apply(&self, state: &mut AppState) {
+        state.canvas_pan_x = self.canvas_pan_x;
+        state.canvas_pan_y = self.canvas_pan_y;
+        state.canvas_zoom = self.canvas_zoom;
+        state.timeline_pan_x = self.timeline_pan_x;
+        state.timeline_pan_y = self.timeline_pan_y;
+        state.timeline_zoom = self.timeline_zoom;
+    }
+}
+
+/// Matches a fresh `AppState`.
+impl Default for ProjectView {
+    fn default() -> Self {
+        Self {
+            canvas_pan_x: 0.0,
+            canvas_pan_y: 0.0,
+            canvas_zoom: 1.0,
+            timeline_pan_x: 0.0,
+            timeline_pan_y: 0.0,
+            timeline_zoom: 100.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewStore {
+    pub version: u32,
+    pub layout: Layout,
+    /// Keyed by project folder.
+    pub projects: BTreeMap<String, ProjectView>,
+}
+
+impl Default for ViewStore {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            layout: Layout::default(),
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+fn project_key(dir: &Path) -> String {
+    dir.to_string_lossy().into_owned()
+}
+
+impl ViewStore {
+    /// Read the stored document; anything unusable gives the defaults.
+    pub fn load(storage: &dyn eframe::Storage) -> Self {
+        storage
+            .get_string(STORAGE_KEY)
+            .and_then(|text| serde_json::from_str::<Self>(&text).ok())
+            .filter(|store| store.version <= SCHEMA_VERSION)
+            .map(|store| Self {
+                version: SCHEMA_VERSION,
+                ..store
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        if let Ok(text) = serde_json::to_string(self) {
+            storage.set_string(STORAGE_KEY, text);
+        }
+    }
+
+    /// Take the current layout, and the view of the open project, from
+    /// `state`.
+    pub fn capture(&mut self, state: &AppState) {
+        self.layout = Layout::of(state);
+        if let Some(dir) = &state.project_path {
+            self.projects
+                .insert(project_key(dir), ProjectView::of(state));
+        }
+    }
+
+    pub fn apply_layout(&self, state: &mut AppState) {
+        self.layout.apply(state);
+    }
+
+    /// Restore the view last used for the project in `dir`, or the default
+    /// view for a project opened for the first time.
+    pub fn apply_project(&self, state: &mut AppState, dir: &Path) {
+        self.projects
+            .get(&project_key(dir))
+            .cloned()
+            .unwrap_or_default()
+            .apply(state);
+    }
+}
+
+// ─── Storage ────────────────────────────────────────────────────────────────
+
+/// [`eframe::Storage`] backed by a JSON file, used when eframe provides no
+/// storage of its own (it is built without its `persistence` feature).
+#[derive(Debug, Default)]
+pub struct FileStorage {
+    file: PathBuf,
+    values: HashMap<String, String>,
+    dirty: bool,
+}
+
+impl FileStorage {
+    /// Load `file`; a missing or unreadable file starts empty.
+    pub fn load(file: PathBuf) -> Self {
+        let values = std::fs::read_to_string(&file)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            file,
+            values,
+            dirty: false,
+        }
+    }
+}
+
+impl eframe::Storage for FileStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        if self.values.get(key) != Some(&value) {
+            self.values.insert(key.to_string(), value);
+            self.dirty = true;
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        if let Some(dir) = self.file.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(text) = serde_json::to_string_pretty(&self.values) {
+            if crate::project::write_atomic(&self.file, text.as_bytes()).is_ok() {
+                self.dirty = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::Storage;
+
+    #[test]
+    fn defaults_match_a_fresh_state() {
+        let state = AppState::default();
+        assert_eq!(Layout::default(), Layout::of(&state));
+        assert_eq!(ProjectView::default(), ProjectView::of(&state));
+    }
+
+    #[test]
+    fn restores_layout_and_per_project_view() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("ui_state.json");
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+
+        let mut state = AppState {
+            left_panel_width: 320.0,
+            active_tab: None,
+            canvas_zoom: 2.5,
+            timeline_zoom: 40.0,
+            project_path: Some(a.clone()),
+            ..AppState::default()
+        };
+        state.history.memory_limit = 8 << 20;
+        let mut store = ViewStore::default();
+        store.capture(&state);
+        state.project_path = Some(b.clone());
+        state.canvas_zoom = 0.5;
+        store.capture(&state);
+
+        let mut storage = FileStorage::load(file.clone());
+        store.save(&mut storage);
+        storage.flush();
+
+        let loaded = ViewStore::load(&FileStorage::load(file));
+        assert_eq!(loaded, store);
+        let mut fresh = AppState::default();
+        loaded.apply_layout(&mut fresh);
+        assert_eq!(fresh.left_panel_width, 320.0);
+        assert_eq!(fresh.active_tab, None);
+        assert_eq!(fresh.history.memory_limit, 8 << 20);
+        loaded.apply_project(&mut fresh, &a);
+        assert_eq!((fresh.canvas_zoom, fresh.timeline_zoom), (2.5, 40.0));
+        loaded.apply_project(&mut fresh, &b);
+        assert_eq!(fresh.canvas_zoom, 0.5);
+        loaded.apply_project(&mut fresh, &dir.path().join("new"));
+        assert_eq!(fresh.canvas_zoom, 1.0);
+    }
+
+    #[test]
+    fn ignores_newer_or_broken_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = FileStorage::load(dir.path().join("ui_state.json"));
+
+        storage.set_string(
+            STORAGE_KEY,
+            r#"{"version": 99, "layout": {"left_panel_width": 10}}"#.into(),
+        );
+        assert_eq!(ViewStore::load(&storage), ViewStore::default());
+
+        storage.set_string(STORAGE_KEY, r#"{"layout": {"active_tab": 7}}"#.into());
+        assert_eq!(ViewStore::load(&storage), ViewStore::default());
+
+        // fields added later fall back to their defaults
+        storage.set_string(
+            STORAGE_KEY,
+            r#"{"version": 1, "layout": {"left_panel_width": 250}}"#.into(),
+        );
+        let store = ViewStore::load(&storage);
+        assert_eq!(store.layout.left_panel_width, 250.0);
+        assert_eq!(store.layout.last_active_tab, PanelTab::Code);
+    }
+}
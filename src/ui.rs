@@ -1,9 +1,16 @@
 use crate::app_state::AppState;
+use crate::states::view_state::{FileStorage, ViewStore};
 use crate::{canvas, code_panel, timeline};
 use eframe::egui;
 
+/// How often the view state is written when eframe has no storage.
+const VIEW_SAVE_INTERVAL_SECS: f64 = 30.0;
+
 pub struct MyApp {
     state: AppState,
+    /// Used for the view state when eframe provides no storage.
+    storage: Option<FileStorage>,
+    next_view_save: f64,
 }
 
 pub fn create_app(cc: &eframe::CreationContext<'_>) -> MyApp {
@@ -14,6 +21,23 @@ pub fn create_app(cc: &eframe::CreationContext<'_>) -> MyApp {
     state.folder_dialog_tx = Some(tx);
     state.folder_dialog_rx = Some(rx);
 
+    if let Some(file) = crate::states::recent_projects::default_file() {
+        state.recent_projects = crate::states::recent_projects::RecentProjects::load(file);
+    }
+
+    // Restore the panel layout; project views are applied on open
+    let storage = match cc.storage {
+        Some(_) => None,
+        None => crate::states::config_dir().map(|dir| FileStorage::load(dir.join("ui_state.json"))),
+    };
+    let views = match (cc.storage, &storage) {
+        (Some(storage), _) => ViewStore::load(storage),
+        (None, Some(storage)) => ViewStore::load(storage),
+        (None, None) => ViewStore::default(),
+    };
+    views.apply_layout(&mut state);
+    state.view_store = views;
+
     // Initialize logo texture
     if let Some(logo_image) = crate::logo::color_image_from_svg(include_str!("../assets/logo.svg"))
     {
@@ -24,16 +48,47 @@ pub fn create_app(cc: &eframe::CreationContext<'_>) -> MyApp {
         ));
     }
 
-    MyApp { state }
+    MyApp {
+        state,
+        storage,
+        next_view_save: VIEW_SAVE_INTERVAL_SECS,
+    }
 }
 
 impl eframe::App for MyApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let mut views = std::mem::take(&mut self.state.view_store);
+        views.capture(&self.state);
+        views.save(storage);
+        self.state.view_store = views;
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.show(ctx);
+
+        let now = ctx.input(|i| i.time);
+        let closing = ctx.input(|i| i.viewport().close_requested());
+        if closing || now >= self.next_view_save {
+            self.next_view_save = now + VIEW_SAVE_INTERVAL_SECS;
+            if let Some(mut storage) = self.storage.take() {
+                eframe::App::save(self, &mut storage);
+                eframe::Storage::flush(&mut storage);
+                self.storage = Some(storage);
+            }
+        }
+    }
+}
+
+impl MyApp {
+    fn show(&mut self, ctx: &egui::Context) {
         let state = &mut self.state;
         let now = ctx.input(|i| i.time);
         let _ = state.tick(now);
 
         let is_modal_open = state.show_welcome || state.show_settings;
+        if !state.show_welcome {
+            crate::states::history::handle_shortcuts(ctx, state, now);
+        }
 
         // Sidebar Toolbar (Far Left)
         egui::SidePanel::left("toolbar_panel")
@@ -66,16 +121,11 @@ impl eframe::App for MyApp {
                             code_panel::show(ui, state);
                         }
                         crate::app_state::PanelTab::SceneGraph => {
-                            ui.vertical(|ui| {
-                                ui.set_min_size(ui.available_size());
-                                ui.add_space(8.0);
-                                ui.heading("Scene Graph");
-                                ui.label("Coming soon...");
-                            });
+                            crate::scene_graph::show(ui, state);
                         }
                     }
                 });
-            
+
             // Update the width so if the user resizes it, we remember it.
             state.left_panel_width = panel_res.response.rect.width();
         }
@@ -93,6 +143,20 @@ impl eframe::App for MyApp {
             ctx.request_repaint();
         }
 
+        if state.show_export && !is_modal_open {
+            crate::modals::export_modal::show(ctx, state);
+        }
+
+        if state.project_path.is_some() {
+            // keep polling the entry file while the window is idle
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                crate::states::file_watch::POLL_INTERVAL_SECS,
+            ));
+        }
+        if !is_modal_open {
+            crate::modals::file_conflict_modal::show(ctx, state);
+        }
+
         if state.show_welcome {
             crate::modals::welcome_modal::show(ctx, state);
         } else if state.show_settings {